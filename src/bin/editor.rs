@@ -10,6 +10,11 @@ fn main() -> eframe::Result {
     env_logger::init();
 
     let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("migrate") {
+        migrate(args.get(2..).unwrap_or_default());
+        return Ok(());
+    }
+
     let project_path = args
         .get(1)
         .cloned()
@@ -33,6 +38,60 @@ fn main() -> eframe::Result {
     )
 }
 
+/// `editor migrate <project> [--dry-run]`: brings every document of a project up to
+/// the current version of its schema.
+#[cfg(not(target_arch = "wasm32"))]
+#[expect(
+    clippy::print_stdout,
+    clippy::print_stderr,
+    reason = "reports go to the terminal"
+)]
+fn migrate(args: &[String]) {
+    use std::path::PathBuf;
+
+    use atom_editor::editor::{
+        files::project::Project,
+        form::{migration::migrate_file, schema::SchemaRegistry},
+    };
+
+    let dry_run = args.iter().any(|a| a == "--dry-run");
+    let Some(root) = args
+        .iter()
+        .find(|a| !a.starts_with("--"))
+        .map(PathBuf::from)
+    else {
+        eprintln!("Usage: editor migrate <project> [--dry-run]");
+        return;
+    };
+    let project = match Project::from(&root) {
+        Ok(project) => project.load(),
+        Err(err) => {
+            eprintln!("Cannot open project {}: {:?}", root.display(), err);
+            return;
+        }
+    };
+
+    let schemas = SchemaRegistry::new(Some(&root));
+    for path in project.document_paths() {
+        match migrate_file(&schemas, &path, dry_run) {
+            Ok(Some(report)) => {
+                println!(
+                    "{}: v{} -> v{}{}",
+                    report.path.display(),
+                    report.from,
+                    report.to,
+                    if dry_run { " (dry run)" } else { "" }
+                );
+                for change in report.changes {
+                    println!("  {change}");
+                }
+            }
+            Ok(None) => {}
+            Err(err) => eprintln!("{}: {}", path.display(), err),
+        }
+    }
+}
+
 // WASM
 #[cfg(target_arch = "wasm32")]
 fn main() {
@@ -0,0 +1,960 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use egui::{Response, Slider, Ui};
+use egui_ltreeview::{TreeView, TreeViewBuilder, TreeViewState};
+
+use crate::editor::{
+    components::{
+        batch_form::BatchForm, diff_view::DiffView, form_config::FormConfig, form_view::Form,
+        help_view, localization_view::LocalizationView, sections::Sections,
+        widgets::WidgetRegistry,
+    },
+    config::Config,
+    files::project::{File, Project},
+    form::{
+        export::{ExportOptions, export_document},
+        form::DocumentError,
+        migration::{MigrationPlan, migrate_file},
+        options::{DEFAULT_MANIFEST, MANIFEST_FILE, ProjectOptions},
+        schema::SchemaRegistry,
+    },
+    history::History,
+};
+
+/// We derive Deserialize/Serialize so we can persist app state on shutdown.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)] // if we add new fields, give them default values when deserializing old state
+pub struct EditorApp {
+    // Config
+    config: Config,
+    form_config: FormConfig,
+    base_folder: Option<String>,
+
+    // Sub Windows
+    show_settings: bool,
+    show_help: bool,
+    show_outline: bool,
+
+    // Expanded tables of each document the project opened
+    sections: HashMap<PathBuf, Sections>,
+
+    // Current project
+    #[serde(skip)]
+    project: Option<Project>,
+
+    // File tree state
+    #[serde(skip)]
+    tree_state: TreeViewState<i32>,
+
+    // Open forms
+    #[serde(skip)]
+    documents: Vec<Form>,
+
+    // Selected file node
+    #[serde(skip)]
+    current_selected: i32,
+
+    // Batch edit of the selected documents
+    #[serde(skip)]
+    batch: Option<BatchForm>,
+
+    // Translations of the whole project
+    #[serde(skip)]
+    localization: Option<LocalizationView>,
+
+    // Custom field editors, by `widget` hint and field type
+    #[serde(skip)]
+    widgets: WidgetRegistry,
+
+    // Changes of the open document against its file or git
+    #[serde(skip)]
+    diff: Option<DiffView>,
+
+    // Undo / redo of changes written to disk
+    #[serde(skip)]
+    history: History,
+
+    // Schemas of the current project and the bundled ones
+    #[serde(skip)]
+    schemas: SchemaRegistry,
+
+    // Manifest and documents enum option sets are resolved against
+    #[serde(skip)]
+    project_options: ProjectOptions,
+
+    // Document waiting for the user to confirm its schema migration
+    #[serde(skip)]
+    pending_migration: Option<PendingMigration>,
+}
+
+/// A document opened with an outdated schema version.
+struct PendingMigration {
+    path: PathBuf,
+    schema: String,
+    from: u32,
+    to: u32,
+    changes: Vec<String>,
+}
+impl Default for EditorApp {
+    fn default() -> Self {
+        // Resolved through the registry, which strips the schema's version and migrations.
+        let schemas = SchemaRegistry::default();
+        let document = schemas
+            .load_schema("block")
+            .expect("the bundled block schema is valid");
+
+        Self {
+            // Example stuff:
+            project: None,
+            tree_state: TreeViewState::default(),
+            documents: vec![Form::new(document)],
+            current_selected: 0,
+            config: Config::default(),
+            form_config: FormConfig::default(),
+            show_settings: false,
+            show_help: true,
+            show_outline: true,
+            sections: HashMap::new(),
+            base_folder: None,
+            batch: None,
+            localization: None,
+            diff: None,
+            widgets: WidgetRegistry::default(),
+            history: History::default(),
+            schemas,
+            project_options: ProjectOptions::default(),
+            pending_migration: None,
+        }
+    }
+}
+
+impl EditorApp {
+    /// Called once before the first frame.
+    pub fn new(cc: &eframe::CreationContext<'_>, project_folder: PathBuf) -> Self {
+        let storage_key = format!(
+            "editor_{}",
+            project_folder.to_string_lossy().replace(['/', '\\'], "_")
+        );
+
+        let mut app = if let Some(storage) = cc.storage {
+            // Try load previous state for THIS project
+            eframe::get_value::<Self>(storage, &storage_key).unwrap_or_else(|| EditorApp::default())
+        } else {
+            EditorApp::default()
+        };
+
+        app.base_folder = Some(project_folder.to_string_lossy().to_string());
+        app.open_project(&project_folder);
+
+        app
+    }
+
+    /// The widgets forms draw fields with; register project-specific ones here.
+    pub fn widgets_mut(&mut self) -> &mut WidgetRegistry {
+        &mut self.widgets
+    }
+
+    pub fn open_and_create_project(&mut self, path: &PathBuf) {
+        let root_folder = PathBuf::from(path);
+
+        if root_folder.is_dir() {
+            fs::create_dir(root_folder.join("data")).ok();
+            fs::create_dir(root_folder.join("recipes")).ok();
+            fs::create_dir(root_folder.join("assets")).ok();
+            fs::create_dir(root_folder.join("schemas")).ok();
+            let manifest = root_folder.join(MANIFEST_FILE);
+            if !manifest.exists() {
+                fs::write(manifest, DEFAULT_MANIFEST).ok();
+            }
+
+            self.project = Some(
+                Project::from(&root_folder)
+                    .expect("AAAAAAAAAAAAAAAAA")
+                    .load(),
+            );
+            self.schemas = SchemaRegistry::new(Some(&root_folder));
+            self.refresh_project_options();
+        }
+    }
+    pub fn open_project(&mut self, path: &PathBuf) {
+        let root_folder = PathBuf::from(path);
+
+        if root_folder.is_dir() {
+            self.project = Some(
+                Project::from(&root_folder)
+                    .expect("AAAAAAAAAAAAAAAAA")
+                    .load(),
+            );
+            self.schemas = SchemaRegistry::new(Some(&root_folder));
+            self.refresh_project_options();
+        }
+    }
+
+    /// Reloads the manifest and project documents behind `options_from` sets.
+    pub fn refresh_project_options(&mut self) {
+        let Some(project) = &self.project else {
+            return;
+        };
+        self.project_options =
+            ProjectOptions::load(&project.path, &project.document_paths(), &self.schemas);
+        for form in &mut self.documents {
+            form.refresh_options(&self.project_options);
+        }
+    }
+
+    pub fn project_tree(&mut self, ui: &mut egui::Ui) {
+        if let Some(project) = &self.project {
+            let id = ui.make_persistent_id(project.name.clone());
+            let (response, actions) =
+                TreeView::new(id).show_state(ui, &mut self.tree_state, |builder| {
+                    builder.dir(0, "Root");
+                    Self::build_project_tree_static(builder, &project.files.children);
+                    builder.close_dir();
+                });
+            let selected_documents: Vec<PathBuf> = self
+                .tree_state
+                .selected()
+                .iter()
+                .filter_map(|id| project.get_file(*id))
+                .filter(|file| file.is_document())
+                .map(|file| file.path.clone())
+                .collect();
+
+            if selected_documents.len() > 1 {
+                self.show_selection_options_popup(&selected_documents, &response);
+            } else if let Some(selected) = self.tree_state.selected().first() {
+                println!("ID seleccionado del TreeView: {}", selected);
+                if let Some(file) = project.get_file(*selected) {
+                    if file.id != self.current_selected && !file.is_folder {
+                        self.current_selected = file.id;
+                    }
+                    self.show_file_options_popup(ui, &file.path.clone(), file.id, response);
+                } else {
+                    println!("No se encontró archivo con ID: {}", selected);
+                }
+            }
+        }
+    }
+
+    fn build_project_tree_static(builder: &mut TreeViewBuilder<'_, i32>, files: &[File]) {
+        for file in files {
+            if file.is_folder {
+                builder.dir(file.id, &file.name);
+                Self::build_project_tree_static(builder, &file.children);
+                builder.close_dir();
+            } else {
+                builder.leaf(file.id, &file.name);
+            }
+        }
+    }
+
+    pub fn show_selection_options_popup(&mut self, paths: &[PathBuf], response: &Response) {
+        egui::Popup::menu(response)
+            .kind(egui::PopupKind::Menu)
+            .layout(egui::Layout::top_down_justified(egui::Align::Min))
+            .align(egui::RectAlign::BOTTOM_START)
+            .gap(2.0)
+            .show(|ui| {
+                if ui
+                    .button(format!("Batch edit {} files", paths.len()))
+                    .clicked()
+                {
+                    self.localization = None;
+                    self.batch = Some(BatchForm::open(
+                        paths.to_vec(),
+                        &self.schemas,
+                        &self.project_options,
+                    ));
+                    ui.close();
+                }
+            });
+    }
+
+    pub fn undo(&mut self) {
+        let errors = self.history.undo();
+        Self::log_write_errors(errors);
+    }
+
+    pub fn redo(&mut self) {
+        let errors = self.history.redo();
+        Self::log_write_errors(errors);
+    }
+
+    fn log_write_errors(errors: Vec<(PathBuf, DocumentError)>) {
+        for (path, err) in errors {
+            log::error!("Failed to write {}: {}", path.display(), err);
+        }
+    }
+
+    /// Reads the files a batch edit, a localization save, an undo or a redo rewrote
+    /// back into every view showing them, so a later save does not write stale
+    /// documents over them.
+    fn reload_written_files(&mut self) {
+        let written = self.history.take_written();
+        if written.is_empty() {
+            return;
+        }
+        for form in &mut self.documents {
+            let Some(path) = form
+                .path()
+                .filter(|path| written.iter().any(|w| w == path))
+                .map(Path::to_path_buf)
+            else {
+                continue;
+            };
+            match self.schemas.load_document(&path) {
+                Ok(document) => form.replace_document(document),
+                Err(err) => log::error!("Failed to reload {}: {}", path.display(), err),
+            }
+        }
+        self.refresh_project_options();
+        if let Some(batch) = &mut self.batch {
+            batch.reload(&self.schemas, &self.project_options);
+        }
+        if let Some(localization) = &mut self.localization {
+            localization.reload(&self.schemas);
+        }
+        if let Some(diff) = &mut self.diff {
+            diff.invalidate();
+        }
+    }
+
+    /// Shows the translations of every project document in place of the form.
+    pub fn open_localization(&mut self) {
+        let Some(project) = &self.project else {
+            return;
+        };
+        self.batch = None;
+        self.localization = Some(LocalizationView::open(
+            project.document_paths(),
+            &self.schemas,
+        ));
+    }
+
+    /// Opens the document at `path`, asking first if its schema version is outdated.
+    pub fn open_document(&mut self, path: &Path) {
+        let plan = fs::read_to_string(path)
+            .map_err(DocumentError::from)
+            .and_then(|text| Ok(toml::from_str::<toml::Table>(&text)?))
+            .and_then(|table| {
+                let plan = MigrationPlan::for_document(&self.schemas, &table, path)?;
+                Ok(plan.map(|plan| (plan.preview(&table), plan)))
+            });
+
+        match plan {
+            Ok(Some((changes, plan))) => {
+                self.pending_migration = Some(PendingMigration {
+                    path: path.to_path_buf(),
+                    schema: plan.schema,
+                    from: plan.from,
+                    to: plan.to,
+                    changes,
+                });
+            }
+            Ok(None) => self.load_document(path),
+            Err(err) => log::error!("Failed to open {}: {}", path.display(), err),
+        }
+    }
+
+    fn load_document(&mut self, path: &Path) {
+        match self.schemas.load_document(path) {
+            Ok(document) => {
+                let mut form = Form::open(document, path.to_path_buf());
+                form.refresh_options(&self.project_options);
+                let show_raw = self.documents.first().is_some_and(Form::shows_raw);
+                form.set_show_raw(show_raw);
+                self.remember_sections();
+                if let Some(sections) = self.sections.get(path) {
+                    form.set_sections(sections.clone());
+                }
+                self.batch = None;
+                self.localization = None;
+                self.documents = vec![form];
+            }
+            Err(err) => log::error!("Failed to open {}: {}", path.display(), err),
+        }
+    }
+
+    /// Opens the find bar of the open document, unless a batch edit or the localization
+    /// view is shown in its place.
+    pub fn find_in_form(&mut self) {
+        if self.batch.is_some() || self.localization.is_some() {
+            return;
+        }
+        if let Some(form) = self.documents.first_mut() {
+            form.open_search();
+        }
+    }
+
+    /// Stores which tables of the open document are expanded, to restore them when it is
+    /// opened again.
+    fn remember_sections(&mut self) {
+        for form in &self.documents {
+            if let Some(path) = form.path() {
+                self.sections
+                    .insert(path.to_path_buf(), form.sections().clone());
+            }
+        }
+    }
+
+    /// Writes the open document back to its file, as a change that can be undone.
+    pub fn save_current_document(&mut self) {
+        let Some(form) = self.documents.first_mut().filter(|form| form.is_dirty()) else {
+            return;
+        };
+        match form.save() {
+            Ok(change) => self.history.push(change),
+            Err(err) => log::error!("Failed to save document: {err}"),
+        }
+    }
+
+    pub fn migration_window(&mut self, ctx: &egui::Context) {
+        let Some(pending) = &self.pending_migration else {
+            return;
+        };
+
+        let mut choice = None;
+        let mut cancel = false;
+        egui::Window::new("Migrate document")
+            .collapsible(false)
+            .resizable(true)
+            .default_width(400.0)
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "{} uses version {} of schema `{}`, the current version is {}.",
+                    pending.path.display(),
+                    pending.from,
+                    pending.schema,
+                    pending.to
+                ));
+                ui.add_space(8.0);
+                ui.label("Migrating will make these changes:");
+                egui::ScrollArea::vertical()
+                    .max_height(240.0)
+                    .show(ui, |ui| {
+                        for change in &pending.changes {
+                            ui.label(format!("• {change}"));
+                        }
+                    });
+                ui.add_space(8.0);
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("Migrate and open").clicked() {
+                        choice = Some(true);
+                    }
+                    if ui.button("Open as is").clicked() {
+                        choice = Some(false);
+                    }
+                    if ui.button("Cancel").clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+
+        if cancel {
+            self.pending_migration = None;
+        }
+        let Some(migrate) = choice else {
+            return;
+        };
+        let Some(pending) = self.pending_migration.take() else {
+            return;
+        };
+        if migrate {
+            if let Err(err) = migrate_file(&self.schemas, &pending.path, false) {
+                log::error!("Failed to migrate {}: {}", pending.path.display(), err);
+                return;
+            }
+        }
+        self.load_document(&pending.path);
+    }
+
+    /// Creates a new document from `schema` inside `folder` and refreshes the project tree.
+    pub fn create_document(&mut self, folder: &Path, schema: &str) {
+        // The schema's `meta.format_version` makes the new document current.
+        let document = match self.schemas.load_schema(schema) {
+            Ok(mut document) => {
+                document.schema = Some(schema.to_owned());
+                document
+            }
+            Err(err) => {
+                log::error!("Failed to load schema {schema}: {err}");
+                return;
+            }
+        };
+
+        let mut path = folder.join(format!("new_{schema}.toml"));
+        let mut counter = 1;
+        while path.exists() {
+            counter += 1;
+            path = folder.join(format!("new_{schema}_{counter}.toml"));
+        }
+
+        // Starting from the schema's text keeps its comments in the new document.
+        let text = self
+            .schemas
+            .schema_text(schema)
+            .map_err(DocumentError::from)
+            .and_then(|text| Ok(document.to_toml_preserving(&text)?));
+        let result = match text {
+            Ok(text) => fs::write(&path, text).map_err(DocumentError::from),
+            Err(_) => document.save(&path),
+        };
+        if let Err(err) = result {
+            log::error!("Failed to create {}: {}", path.display(), err);
+        } else if let Some(project) = &self.project {
+            let root = project.path.clone();
+            self.open_project(&root);
+        }
+    }
+
+    /// Help of the field last focused in `form`, with buttons filling in its examples.
+    fn help_panel(ui: &mut Ui, form: &mut Form) {
+        let Some((path, field)) = form.help_field() else {
+            ui.label("Click a field to see its help.");
+            return;
+        };
+        let path = path.to_owned();
+        if let Some(example) = help_view::show(ui, field, &path) {
+            if let Err(err) = form.set_field_value(&path, example) {
+                log::error!("Failed to use the example for {path}: {err}");
+            }
+        }
+    }
+
+    /// Writes the data of the open document, without schema information, to a TOML file.
+    pub fn export_current_document(&self) {
+        let Some(form) = self.documents.first() else {
+            return;
+        };
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("TOML", &["toml"])
+            .save_file()
+        else {
+            return;
+        };
+
+        let options = ExportOptions {
+            omit_hidden: self.form_config.omit_hidden_on_export,
+            omit_defaults: self.form_config.omit_defaults_on_export,
+        };
+        let table = export_document(form.document(), options);
+        let result = toml::to_string(&table)
+            .map_err(DocumentError::from)
+            .and_then(|content| fs::write(&path, content).map_err(DocumentError::from));
+        if let Err(err) = result {
+            log::error!("Failed to export {}: {}", path.display(), err);
+        }
+    }
+
+    pub fn show_file_options_popup(
+        &mut self,
+        ui: &mut Ui,
+        path: &PathBuf,
+        file_id: i32,
+        response: Response,
+    ) {
+        let name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("<unknown>");
+
+        egui::Popup::menu(&response)
+            .kind(egui::PopupKind::Menu)
+            .layout(egui::Layout::top_down_justified(egui::Align::Min))
+            .align(egui::RectAlign::BOTTOM_START)
+            .gap(2.0)
+            .show(|ui| {
+                if path.is_dir() {
+                    // A schema that fails to load could not create anything.
+                    let mut schemas = self.schemas.names();
+                    schemas.retain(|name| self.schemas.load_schema(name).is_ok());
+                    for schema in schemas {
+                        if ui.button(format!("Create {schema}")).clicked() {
+                            self.create_document(path, &schema);
+                            ui.close();
+                        }
+                    }
+                    if ui.button("Delete").clicked() {
+                        ui.close_menu();
+                    }
+                } else {
+                    if ui.button("Open").clicked() {
+                        self.open_document(path);
+                        ui.close();
+                    }
+                    if ui.button("Duplicate").clicked() {
+                        ui.close_menu();
+                    }
+                    if ui.button("Delete").clicked() {
+                        ui.close_menu();
+                    }
+                }
+            });
+    }
+    pub fn central_panel_content(&mut self, ui: &mut egui::Ui) {
+        let available_size = ui.available_size();
+        ui.horizontal(|ui| {
+            ui.allocate_ui_with_layout(
+                egui::vec2(available_size.x * 0.22, available_size.y),
+                egui::Layout::top_down(egui::Align::LEFT),
+                |ui| {
+                    ui.add_space(12.0);
+                    ui.horizontal(|ui| {
+                        ui.add_space(8.0);
+                        ui.heading("📝 Forms");
+                    });
+                    ui.add_space(8.0);
+                    ui.separator();
+                    ui.add_space(8.0);
+
+                    let scroll_height = available_size.y - 80.0;
+                    egui::ScrollArea::vertical()
+                        .id_salt("schema_list_scroll")
+                        .auto_shrink([false, false])
+                        .max_height(scroll_height)
+                        .show(ui, |ui| {
+                            ui.add_space(4.0);
+                            self.project_tree(ui);
+                        });
+                },
+            );
+
+            ui.separator();
+
+            ui.allocate_ui_with_layout(
+                egui::vec2(available_size.x * 0.75, available_size.y),
+                egui::Layout::top_down(egui::Align::LEFT),
+                |ui| {
+                    ui.add_space(8.0);
+
+                    let mut content_height = available_size.y - 80.0;
+                    let editing_form = self.batch.is_none() && self.localization.is_none();
+                    if let (true, Some(form)) = (editing_form, self.documents.first_mut()) {
+                        let top = ui.cursor().top();
+                        if self.show_outline {
+                            ui.horizontal(|ui| {
+                                ui.add_space(8.0);
+                                form.show_outline(ui);
+                            });
+                        }
+                        if form.is_searching() {
+                            ui.horizontal(|ui| {
+                                ui.add_space(8.0);
+                                form.show_search_bar(ui);
+                            });
+                        }
+                        if self.show_outline || form.is_searching() {
+                            ui.separator();
+                        }
+                        content_height -= ui.cursor().top() - top;
+                    }
+                    egui::ScrollArea::vertical()
+                        .id_salt("tab_content_scroll")
+                        .auto_shrink([false, false])
+                        .max_height(content_height)
+                        .show(ui, |ui| {
+                            ui.add_space(8.0);
+                            ui.horizontal(|ui| {
+                                ui.add_space(8.0);
+                                ui.vertical(|ui| {
+                                    /*
+                                    egui::TextEdit::multiline(&mut self.content)
+                                        .desired_width(f32::INFINITY)
+                                        .font(egui::TextStyle::Monospace),
+                                     */
+                                    if let Some(localization) = &mut self.localization {
+                                        if !localization.show(
+                                            ui,
+                                            &self.form_config,
+                                            &mut self.history,
+                                        ) {
+                                            self.localization = None;
+                                            self.refresh_project_options();
+                                        }
+                                    } else if let Some(batch) = &mut self.batch {
+                                        if !batch.show(ui, &self.form_config, &mut self.history) {
+                                            self.batch = None;
+                                        }
+                                    } else if let Some(form) = self.documents.first_mut() {
+                                        form.show_state(ui, &self.form_config, &self.widgets);
+                                    }
+                                });
+                            });
+                        });
+                },
+            );
+        });
+    }
+    pub fn form_config_window(&mut self, ctx: &egui::Context) {
+        egui::Window::new("⚙️ Form Settings")
+            .collapsible(false)
+            .resizable(true)
+            .default_width(400.0)
+            .show(ctx, |ui| {
+                ui.add_space(8.0);
+                ui.heading("Font size");
+                ui.add_space(12.0);
+                ui.horizontal(|ui| {
+                    ui.label("Headers:");
+                    ui.add(
+                        Slider::new(&mut self.form_config.font_size_header, 10.0..=32.0).text("px"),
+                    );
+                });
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    ui.label("Tags:");
+                    ui.add(
+                        Slider::new(&mut self.form_config.font_size_label, 10.0..=32.0).text("px"),
+                    );
+                });
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    ui.label("Descriptions:");
+                    ui.add(
+                        Slider::new(&mut self.form_config.font_size_description, 10.0..=32.0)
+                            .text("px"),
+                    );
+                });
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    ui.label("Text:");
+                    ui.add(
+                        Slider::new(&mut self.form_config.font_size_text, 10.0..=32.0).text("px"),
+                    );
+                });
+                ui.add_space(8.0);
+                ui.checkbox(
+                    &mut self.form_config.inline_descriptions,
+                    "Show descriptions under fields",
+                )
+                .on_hover_text("Otherwise descriptions are shown when hovering a field");
+                ui.add_space(16.0);
+                ui.heading("Export");
+                ui.add_space(8.0);
+                ui.checkbox(
+                    &mut self.form_config.omit_hidden_on_export,
+                    "Omit hidden fields",
+                )
+                .on_hover_text("Leave out fields whose visible_if condition is false");
+                ui.checkbox(
+                    &mut self.form_config.omit_defaults_on_export,
+                    "Only write values that differ from their default",
+                )
+                .on_hover_text("Leave out fields that hold the default their schema declares");
+                ui.add_space(16.0);
+                ui.separator();
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button("Reset to default").clicked() {
+                        self.form_config = FormConfig::default();
+                    }
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.button("Close").clicked() {
+                            self.show_settings = false;
+                        }
+                    });
+                });
+                ui.add_space(8.0);
+            });
+    }
+
+    fn edit_menu(&mut self, ui: &mut Ui) {
+        ui.menu_button("Edit", |ui| {
+            let undo_text = match self.history.undo_label() {
+                Some(label) => format!("Undo {label}"),
+                None => "Undo".to_owned(),
+            };
+            if ui
+                .add_enabled(self.history.can_undo(), egui::Button::new(undo_text))
+                .clicked()
+            {
+                self.undo();
+                ui.close();
+            }
+            let redo_text = match self.history.redo_label() {
+                Some(label) => format!("Redo {label}"),
+                None => "Redo".to_owned(),
+            };
+            if ui
+                .add_enabled(self.history.can_redo(), egui::Button::new(redo_text))
+                .clicked()
+            {
+                self.redo();
+                ui.close();
+            }
+            ui.separator();
+            if ui.button("Find in form (Ctrl+F)").clicked() {
+                self.find_in_form();
+                ui.close();
+            }
+        });
+    }
+
+    pub fn menu_bar(&mut self, ctx: &egui::Context) {
+        egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
+            // The top panel is often a good place for a menu bar:
+
+            egui::MenuBar::new().ui(ui, |ui| {
+                // NOTE: no File->Quit on web pages!
+                let is_web = cfg!(target_arch = "wasm32");
+                if !is_web {
+                    ui.menu_button("File", |ui| {
+                        if ui.button("Open project").clicked() {
+                            if let Some(path) = rfd::FileDialog::new().pick_folder() {
+                                self.base_folder = Some(path.to_string_lossy().to_string());
+                                self.open_project(&path);
+                            }
+                        }
+                        if ui.button("Create project").clicked() {
+                            if let Some(path) = rfd::FileDialog::new().pick_folder() {
+                                self.base_folder = Some(path.to_string_lossy().to_string());
+                                self.open_and_create_project(&path);
+                            }
+                        }
+                        let dirty = self.documents.first().is_some_and(Form::is_dirty);
+                        if ui.add_enabled(dirty, egui::Button::new("Save")).clicked() {
+                            self.save_current_document();
+                        }
+                        if ui.button("Export document").clicked() {
+                            self.export_current_document();
+                        }
+                        if ui.button("Quit").clicked() {
+                            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                        }
+                    });
+                    ui.add_space(16.0);
+                }
+
+                self.edit_menu(ui);
+                ui.add_space(16.0);
+
+                ui.menu_button("View", |ui| {
+                    if let Some(form) = self.documents.first_mut() {
+                        let mut show_raw = form.shows_raw();
+                        if ui.checkbox(&mut show_raw, "TOML side by side").changed() {
+                            form.set_show_raw(show_raw);
+                        }
+                    }
+                    let mut show_diff = self.diff.is_some();
+                    if ui.checkbox(&mut show_diff, "Changes").changed() {
+                        self.diff = show_diff.then(DiffView::default);
+                    }
+                    ui.checkbox(&mut self.show_help, "Field help");
+                    ui.checkbox(&mut self.show_outline, "Section outline");
+                    if let Some(form) = self.documents.first_mut() {
+                        ui.separator();
+                        if ui.button("Expand all sections").clicked() {
+                            form.expand_all();
+                            ui.close();
+                        }
+                        if ui.button("Collapse all sections").clicked() {
+                            form.collapse_all();
+                            ui.close();
+                        }
+                    }
+                });
+                ui.add_space(16.0);
+
+                ui.menu_button("Project", |ui| {
+                    if ui
+                        .add_enabled(self.project.is_some(), egui::Button::new("Localization"))
+                        .clicked()
+                    {
+                        self.open_localization();
+                        ui.close();
+                    }
+                });
+                ui.add_space(16.0);
+
+                egui::widgets::global_theme_preference_buttons(ui);
+
+                if ui.button("⚙️ Settings").clicked() {
+                    self.show_settings = !self.show_settings;
+                }
+            });
+        });
+    }
+}
+
+impl eframe::App for EditorApp {
+    /// Called by the framework to save state before shutdown.
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        self.remember_sections();
+        if let Some(base_folder) = &self.base_folder {
+            let storage_key = format!("editor_{}", base_folder.replace(['/', '\\'], "_"));
+            eframe::set_value(storage, &storage_key, self);
+        }
+    }
+
+    /// Called each time the UI needs repainting, which may be many times per second.
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        // Put your widgets into a `SidePanel`, `TopBottomPanel`, `CentralPanel`, `Window` or `Area`.
+        // For inspiration and more examples, go to https://emilk.github.io/egui
+
+        if self.show_settings {
+            self.form_config_window(ctx);
+        }
+        self.migration_window(ctx);
+
+        let save_shortcut = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::S);
+        if ctx.input_mut(|i| i.consume_shortcut(&save_shortcut)) {
+            self.save_current_document();
+        }
+
+        let find_shortcut = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::F);
+        if ctx.input_mut(|i| i.consume_shortcut(&find_shortcut)) {
+            self.find_in_form();
+        }
+
+        let redo_shortcut = egui::KeyboardShortcut::new(
+            egui::Modifiers::COMMAND | egui::Modifiers::SHIFT,
+            egui::Key::Z,
+        );
+        let undo_shortcut = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Z);
+        // Only while nothing has focus: text fields keep their own undo, and the history
+        // rewrites whole files on disk.
+        if ctx.memory(|m| m.focused().is_none()) {
+            if ctx.input_mut(|i| i.consume_shortcut(&redo_shortcut)) {
+                self.redo();
+            } else if ctx.input_mut(|i| i.consume_shortcut(&undo_shortcut)) {
+                self.undo();
+            }
+        }
+
+        self.menu_bar(ctx);
+
+        let editing_form = self.batch.is_none() && self.localization.is_none();
+        if let (Some(diff), Some(form), true) =
+            (&mut self.diff, self.documents.first_mut(), editing_form)
+        {
+            egui::SidePanel::right("diff_panel")
+                .resizable(true)
+                .default_width(320.0)
+                .show(ctx, |ui| diff.show(ui, form, &self.schemas));
+        }
+
+        if let (true, Some(form), true) = (self.show_help, self.documents.first_mut(), editing_form)
+        {
+            egui::SidePanel::right("help_panel")
+                .resizable(true)
+                .default_width(280.0)
+                .show(ctx, |ui| {
+                    egui::ScrollArea::vertical()
+                        .id_salt("help_scroll")
+                        .show(ui, |ui| Self::help_panel(ui, form));
+                });
+        }
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            // ! FILE TABS
+
+            self.central_panel_content(ui);
+        });
+        self.reload_written_files();
+    }
+}
@@ -0,0 +1,429 @@
+use std::path::PathBuf;
+
+use egui::{FontId, Ui};
+
+use crate::editor::{
+    components::{color_edit, form_config::FormConfig},
+    form::{
+        form::{Document, Field, FieldValue},
+        options::{OptionSets, ProjectOptions},
+        schema::SchemaRegistry,
+    },
+    history::{Change, FileChange, History},
+};
+
+/// A field shared by every document of a batch edit.
+struct BatchField {
+    path: String,
+    label: String,
+    draft: Field,
+    mixed: bool,
+    edited: bool,
+}
+
+/// Edits the common fields of several documents at once.
+pub struct BatchForm {
+    paths: Vec<PathBuf>,
+    documents: Vec<(PathBuf, Document)>,
+    fields: Vec<BatchField>,
+    errors: Vec<String>,
+    /// Option sets of the first document, used by enum fields.
+    options: OptionSets,
+}
+
+impl BatchForm {
+    pub fn open(paths: Vec<PathBuf>, schemas: &SchemaRegistry, project: &ProjectOptions) -> Self {
+        let mut form = Self {
+            paths,
+            documents: Vec::new(),
+            fields: Vec::new(),
+            errors: Vec::new(),
+            options: OptionSets::default(),
+        };
+        form.reload(schemas, project);
+        form
+    }
+
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    /// Reads every selected file again and recomputes the common fields.
+    pub fn reload(&mut self, schemas: &SchemaRegistry, project: &ProjectOptions) {
+        self.documents.clear();
+        self.errors.clear();
+        for path in &self.paths {
+            match schemas.load_document(path) {
+                Ok(document) => self.documents.push((path.clone(), document)),
+                Err(err) => self.errors.push(format!("{}: {}", path.display(), err)),
+            }
+        }
+        self.fields = Self::common_fields(&self.documents);
+        self.options = match self.documents.first() {
+            Some((_, document)) => project.resolve(document),
+            None => OptionSets::default(),
+        };
+    }
+
+    fn common_fields(documents: &[(PathBuf, Document)]) -> Vec<BatchField> {
+        let Some((_, first)) = documents.first() else {
+            return Vec::new();
+        };
+
+        let mut fields = Vec::new();
+        for path in first.leaf_paths() {
+            let Some(field) = first.get(&path) else {
+                continue;
+            };
+            if !Self::is_batch_editable(&field.value) {
+                continue;
+            }
+
+            let mut shared = true;
+            let mut mixed = false;
+            for (_, other) in documents.iter().skip(1) {
+                match other.get(&path) {
+                    Some(other_field) if other_field.value.same_kind(&field.value) => {
+                        mixed |= !other_field.value.value_eq(&field.value);
+                    }
+                    _ => {
+                        shared = false;
+                        break;
+                    }
+                }
+            }
+
+            if shared {
+                let mut draft = field.clone();
+                if let (true, FieldValue::String { value, .. }) = (mixed, &mut draft.value) {
+                    // Show the "mixed" hint instead of the first document's text.
+                    value.clear();
+                }
+                fields.push(BatchField {
+                    label: field.label.clone().unwrap_or_else(|| path.clone()),
+                    path,
+                    draft,
+                    mixed,
+                    edited: false,
+                });
+            }
+        }
+        fields
+    }
+
+    fn is_batch_editable(value: &FieldValue) -> bool {
+        matches!(
+            value,
+            FieldValue::String { .. }
+                | FieldValue::Float { .. }
+                | FieldValue::Integer { .. }
+                | FieldValue::Boolean { .. }
+                | FieldValue::Enum { .. }
+                | FieldValue::Color { .. }
+        )
+    }
+
+    /// Writes the edited fields into every document as one undoable change.
+    fn apply(&mut self, history: &mut History) {
+        let edited: Vec<&BatchField> = self.fields.iter().filter(|f| f.edited).collect();
+        if edited.is_empty() {
+            return;
+        }
+
+        let mut files = Vec::new();
+        self.errors.clear();
+        for (path, document) in &mut self.documents {
+            let before = document.clone();
+            for field in &edited {
+                if let Some(target) = document.get_mut(&field.path) {
+                    target.value.set_value_from(&field.draft.value);
+                    // A value written to an unset optional field sets it.
+                    target.set_unset(false);
+                }
+            }
+            match document.save(path) {
+                Ok(()) => files.push(FileChange {
+                    path: path.clone(),
+                    before,
+                    after: document.clone(),
+                }),
+                Err(err) => {
+                    self.errors.push(format!("{}: {}", path.display(), err));
+                    *document = before;
+                }
+            }
+        }
+
+        if !files.is_empty() {
+            let label = if let [field] = edited.as_slice() {
+                format!("Set {} in {} files", field.path, files.len())
+            } else {
+                format!("Set {} fields in {} files", edited.len(), files.len())
+            };
+            history.push(Change { label, files });
+        }
+
+        for field in &mut self.fields {
+            if field.edited {
+                field.edited = false;
+                field.mixed = false;
+            }
+        }
+    }
+
+    /// Shows the batch form. Returns `false` once the user closes it.
+    pub fn show(&mut self, ui: &mut Ui, form_config: &FormConfig, history: &mut History) -> bool {
+        let label_font = FontId::proportional(form_config.font_size_label);
+        let text_font = FontId::monospace(form_config.font_size_text);
+        let mut open = true;
+
+        ui.horizontal(|ui| {
+            ui.label(
+                egui::RichText::new(format!("Batch edit - {} files", self.documents.len()))
+                    .font(FontId::proportional(form_config.font_size_header))
+                    .strong(),
+            );
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.button("Close").clicked() {
+                    open = false;
+                }
+                let edited = self.fields.iter().filter(|f| f.edited).count();
+                if ui
+                    .add_enabled(edited > 0, egui::Button::new("Apply to all"))
+                    .clicked()
+                {
+                    self.apply(history);
+                }
+            });
+        });
+
+        ui.collapsing("Selected files", |ui| {
+            for (path, _) in &self.documents {
+                ui.label(path.display().to_string());
+            }
+        });
+
+        for error in &self.errors {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+        ui.separator();
+
+        if self.fields.is_empty() {
+            ui.label("The selected documents have no fields in common.");
+            return open;
+        }
+
+        egui::Grid::new("batch_form_grid")
+            .num_columns(3)
+            .striped(true)
+            .spacing([12.0, 6.0])
+            .show(ui, |ui| {
+                for field in &mut self.fields {
+                    ui.label(egui::RichText::new(&field.label).font(label_font.clone()))
+                        .on_hover_text(&field.path);
+
+                    if Self::show_editor(ui, field, &text_font, &self.options) {
+                        field.edited = true;
+                    }
+
+                    if field.edited {
+                        ui.label(egui::RichText::new("edited").strong());
+                    } else if field.mixed {
+                        ui.label(egui::RichText::new("mixed").italics().weak())
+                            .on_hover_text("The selected files have different values");
+                    } else {
+                        ui.label("");
+                    }
+                    ui.end_row();
+                }
+            });
+
+        open
+    }
+
+    fn show_editor(
+        ui: &mut Ui,
+        field: &mut BatchField,
+        font: &FontId,
+        options: &OptionSets,
+    ) -> bool {
+        let mixed = field.mixed && !field.edited;
+        match &mut field.draft.value {
+            FieldValue::Boolean { value, .. } => ui.checkbox(value, "").changed(),
+            FieldValue::Float { value, .. } => {
+                ui.add(egui::DragValue::new(value).speed(0.1)).changed()
+            }
+            FieldValue::Integer { value, .. } => ui.add(egui::DragValue::new(value)).changed(),
+            FieldValue::String { value, .. } => ui
+                .add(
+                    egui::TextEdit::singleline(value)
+                        .font(font.clone())
+                        .hint_text("- mixed -")
+                        .desired_width(300.0),
+                )
+                .changed(),
+            FieldValue::Enum {
+                value,
+                options: own,
+                options_from,
+                ..
+            } => {
+                let options = options.options_for(options_from.as_deref(), own);
+                let mut changed = false;
+                egui::ComboBox::from_id_salt(("batch_enum", &field.path))
+                    .selected_text(if mixed { "- mixed -" } else { value.as_str() })
+                    .show_ui(ui, |ui| {
+                        for option in &options {
+                            changed |= ui
+                                .selectable_value(value, option.clone(), option.as_str())
+                                .clicked();
+                        }
+                    });
+                changed
+            }
+            FieldValue::Color { value, alpha, .. } => {
+                let alpha = alpha.unwrap_or(false);
+                let id = ui.make_persistent_id(("batch_color", &field.path));
+                ui.horizontal(|ui| {
+                    // A mixed color has no single value to preview yet.
+                    if !mixed {
+                        color_edit::swatch(ui, value, alpha);
+                    }
+                    color_edit::color_edit(ui, id, value, alpha, font)
+                })
+                .inner
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        path::{Path, PathBuf},
+    };
+
+    use super::BatchForm;
+    use crate::editor::{
+        form::{
+            form::{Document, FieldValue},
+            options::ProjectOptions,
+            schema::SchemaRegistry,
+        },
+        history::History,
+    };
+
+    const STONE: &str = r#"
+[data]
+type = "table"
+name = { type = "string", value = "Stone" }
+hardness = { type = "float", value = 1.5 }
+light = { type = "float", value = 0.0 }
+sound = { type = "string", value = "stone", optional = true, unset = true }
+only_stone = { type = "integer", value = 1 }
+"#;
+
+    const GRANITE: &str = r#"
+[data]
+type = "table"
+name = { type = "string", value = "Granite" }
+hardness = { type = "float", value = 1.5 }
+light = { type = "string", value = "none" }
+sound = { type = "string", value = "stone", optional = true, unset = true }
+"#;
+
+    /// Writes the stone and granite documents to a fresh folder.
+    fn write_documents(test: &str) -> (PathBuf, Vec<PathBuf>) {
+        let dir =
+            std::env::temp_dir().join(format!("atom_editor_batch_{test}_{}", std::process::id()));
+        fs::create_dir_all(&dir).expect("temp folder");
+        let paths = vec![dir.join("stone.toml"), dir.join("granite.toml")];
+        for (path, text) in paths.iter().zip([STONE, GRANITE]) {
+            fs::write(path, text).expect("document written");
+        }
+        (dir, paths)
+    }
+
+    fn load(path: &Path) -> Document {
+        SchemaRegistry::default()
+            .load_document(path)
+            .expect("valid document")
+    }
+
+    #[test]
+    fn finds_fields_of_the_same_kind_in_every_document() {
+        let documents: Vec<_> = [STONE, GRANITE]
+            .iter()
+            .map(|text| (PathBuf::new(), Document::from_toml(text).expect("valid")))
+            .collect();
+        let fields = BatchForm::common_fields(&documents);
+        let summary: Vec<_> = fields.iter().map(|f| (f.path.as_str(), f.mixed)).collect();
+        assert_eq!(
+            summary,
+            [
+                ("data.name", true),
+                ("data.hardness", false),
+                ("data.sound", false)
+            ],
+            "fields missing from a document or of another type are left out"
+        );
+        assert!(
+            matches!(&fields.first().map(|f| &f.draft.value), Some(FieldValue::String { value, .. }) if value.is_empty()),
+            "mixed strings start blank"
+        );
+    }
+
+    #[test]
+    fn applies_edits_to_every_file_as_one_change() {
+        let (dir, paths) = write_documents("apply");
+        let mut batch = BatchForm::open(
+            paths.clone(),
+            &SchemaRegistry::default(),
+            &ProjectOptions::default(),
+        );
+        for field in &mut batch.fields {
+            let value = match field.path.as_str() {
+                "data.hardness" => toml::Value::Float(3.0),
+                "data.sound" => toml::Value::String("gravel".to_owned()),
+                _ => continue,
+            };
+            field.draft.set_value_toml(value).expect("fits the field");
+            field.edited = true;
+        }
+        let mut history = History::default();
+        batch.apply(&mut history);
+
+        let documents: Vec<_> = paths.iter().map(|path| load(path)).collect();
+        for document in &documents {
+            assert!(
+                matches!(document.get("data.hardness").map(|f| &f.value), Some(FieldValue::Float { value, .. }) if *value == 3.0),
+                "value written"
+            );
+            let sound = document.get("data.sound").expect("sound field");
+            assert!(
+                !sound.is_unset(),
+                "a value written to an unset field sets it"
+            );
+        }
+        assert_eq!(history.take_written(), paths, "written files are reported");
+        assert_eq!(
+            history.undo_label(),
+            Some("Set 2 fields in 2 files"),
+            "one change"
+        );
+
+        history.undo();
+        let restored = load(paths.first().expect("two paths"));
+        fs::remove_dir_all(&dir).ok();
+        assert!(
+            matches!(restored.get("data.hardness").map(|f| &f.value), Some(FieldValue::Float { value, .. }) if *value == 1.5),
+            "undo restores the files"
+        );
+        assert!(
+            restored.get("data.sound").is_none_or(|f| f.is_unset()),
+            "undo leaves the field out of the file again"
+        );
+    }
+}
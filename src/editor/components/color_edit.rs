@@ -0,0 +1,83 @@
+use egui::{Color32, FontId, Ui, color_picker::Alpha};
+
+use crate::editor::form::color::{ColorValue, Rgba};
+
+const SWATCH_SIZE: egui::Vec2 = egui::vec2(16.0, 16.0);
+
+fn color32(color: Rgba) -> Color32 {
+    Color32::from_rgba_unmultiplied(color.r, color.g, color.b, color.a)
+}
+
+/// A small preview of `value`. Invalid colors are drawn as an outlined box with the
+/// error on hover.
+pub fn swatch(ui: &mut Ui, value: &ColorValue, alpha: bool) -> egui::Response {
+    match value.rgba(alpha) {
+        Ok(color) => egui::color_picker::show_color(ui, color32(color), SWATCH_SIZE)
+            .on_hover_text(value.to_text(alpha)),
+        Err(err) => {
+            let (rect, response) = ui.allocate_exact_size(SWATCH_SIZE, egui::Sense::hover());
+            ui.painter().rect_stroke(
+                rect,
+                2.0,
+                egui::Stroke::new(1.0, ui.visuals().error_fg_color),
+                egui::StrokeKind::Inside,
+            );
+            response.on_hover_text(err)
+        }
+    }
+}
+
+/// Color picker button followed by a text box for the stored hex or integer value.
+///
+/// Text that is not a valid color is kept in the box, and left out of `value`, until it
+/// is corrected. Returns whether `value` changed.
+pub fn color_edit(
+    ui: &mut Ui,
+    id: egui::Id,
+    value: &mut ColorValue,
+    alpha: bool,
+    font: &FontId,
+) -> bool {
+    let mut changed = false;
+    let current = value.to_text(alpha);
+    let mut text = ui
+        .data(|d| d.get_temp::<String>(id))
+        .unwrap_or_else(|| current.clone());
+
+    let mut color = color32(value.rgba(alpha).unwrap_or(Rgba::BLACK));
+    let mode = if alpha {
+        Alpha::OnlyBlend
+    } else {
+        Alpha::Opaque
+    };
+    if egui::color_picker::color_edit_button_srgba(ui, &mut color, mode).changed() {
+        let [r, g, b, a] = color.to_srgba_unmultiplied();
+        value.set(Rgba { r, g, b, a }, alpha);
+        text = value.to_text(alpha);
+        changed = true;
+    }
+
+    let response = ui.add(
+        egui::TextEdit::singleline(&mut text)
+            .font(font.clone())
+            .desired_width(90.0),
+    );
+    let parsed = value.parse_like(&text, alpha);
+    if response.changed() {
+        if let Ok(new) = &parsed {
+            changed |= new != value;
+            value.clone_from(new);
+        }
+    }
+
+    // Keep the typed text while it is being edited or invalid, otherwise show the value.
+    if response.has_focus() || parsed.is_err() {
+        ui.data_mut(|d| d.insert_temp(id, text));
+    } else {
+        ui.data_mut(|d| d.remove::<String>(id));
+    }
+    if let Err(err) = parsed.and_then(|_| value.rgba(alpha)) {
+        ui.colored_label(ui.visuals().error_fg_color, err);
+    }
+    changed
+}
@@ -0,0 +1,141 @@
+use std::path::{Path, PathBuf};
+
+use egui::{Color32, Ui};
+
+use crate::editor::{
+    components::form_view::Form,
+    form::{
+        diff::{self, ChangeKind},
+        form::Document,
+        schema::SchemaRegistry,
+    },
+};
+
+/// What the open document is compared with.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum DiffBase {
+    /// The file as last saved.
+    Disk,
+    /// The file as committed in the project's git HEAD.
+    GitHead,
+}
+
+/// Panel listing the fields of the open document that differ from its file or from git.
+pub struct DiffView {
+    base: DiffBase,
+    /// Document the panel compares against, loaded for `loaded_for`.
+    document: Result<Document, String>,
+    loaded_for: Option<(PathBuf, DiffBase)>,
+}
+
+impl Default for DiffView {
+    fn default() -> Self {
+        Self {
+            base: DiffBase::Disk,
+            document: Err(String::new()),
+            loaded_for: None,
+        }
+    }
+}
+
+impl DiffView {
+    /// Reads the base version again, e.g. after the file was saved.
+    pub fn invalidate(&mut self) {
+        self.loaded_for = None;
+    }
+
+    fn load(&mut self, path: &Path, schemas: &SchemaRegistry) {
+        self.document = match self.base {
+            DiffBase::Disk => schemas.load_document(path).map_err(|err| err.to_string()),
+            DiffBase::GitHead => diff::git_head_text(path).and_then(|text| {
+                schemas
+                    .parse_document(&text, path.parent())
+                    .map_err(|err| err.to_string())
+            }),
+        };
+        self.loaded_for = Some((path.to_path_buf(), self.base));
+    }
+
+    pub fn show(&mut self, ui: &mut Ui, form: &mut Form, schemas: &SchemaRegistry) {
+        ui.horizontal(|ui| {
+            ui.heading("Changes");
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui
+                    .button("⟳")
+                    .on_hover_text("Read the base again")
+                    .clicked()
+                {
+                    self.invalidate();
+                }
+            });
+        });
+        ui.horizontal(|ui| {
+            ui.label("Compare with");
+            ui.selectable_value(&mut self.base, DiffBase::Disk, "Saved file");
+            ui.selectable_value(&mut self.base, DiffBase::GitHead, "Git HEAD");
+        });
+        ui.separator();
+
+        let Some(path) = form.path().map(Path::to_path_buf) else {
+            ui.label("The document has no file.");
+            return;
+        };
+        if self.loaded_for.as_ref() != Some(&(path.clone(), self.base)) {
+            self.load(&path, schemas);
+        }
+        let base = match &self.document {
+            Ok(document) => document,
+            Err(err) => {
+                ui.colored_label(ui.visuals().error_fg_color, err);
+                return;
+            }
+        };
+
+        let changes = diff::diff(base, form.document());
+        if changes.is_empty() {
+            ui.label("No changes.");
+            return;
+        }
+
+        let mut reverted = None;
+        egui::ScrollArea::vertical()
+            .id_salt("diff_scroll")
+            .show(ui, |ui| {
+                for change in &changes {
+                    ui.horizontal(|ui| {
+                        let (mark, color) = match change.kind {
+                            ChangeKind::Added => ("+", Color32::from_rgb(90, 170, 90)),
+                            ChangeKind::Removed => ("−", Color32::from_rgb(200, 80, 80)),
+                            ChangeKind::Changed => ("~", Color32::from_rgb(200, 160, 60)),
+                        };
+                        ui.colored_label(color, egui::RichText::new(mark).monospace().strong());
+                        ui.label(egui::RichText::new(&change.path).monospace());
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            if ui.small_button("Revert").clicked() {
+                                reverted = Some(change.clone());
+                            }
+                        });
+                    });
+                    if let Some(old) = &change.old {
+                        ui.label(
+                            egui::RichText::new(format!("  - {old}"))
+                                .monospace()
+                                .color(Color32::from_rgb(200, 80, 80)),
+                        );
+                    }
+                    if let Some(new) = &change.new {
+                        ui.label(
+                            egui::RichText::new(format!("  + {new}"))
+                                .monospace()
+                                .color(Color32::from_rgb(90, 170, 90)),
+                        );
+                    }
+                    ui.add_space(4.0);
+                }
+            });
+
+        if let Some(change) = reverted {
+            form.revert_change(base, &change);
+        }
+    }
+}
@@ -1,11 +1,16 @@
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct FormConfig {
     pub font_size_header: f32,
     pub font_size_label: f32,
     pub font_size_description: f32,
     pub font_size_text: f32,
+    /// Show descriptions under their fields instead of as tooltips.
+    pub inline_descriptions: bool,
+    pub omit_hidden_on_export: bool,
+    pub omit_defaults_on_export: bool,
 }
 
 impl Default for FormConfig {
@@ -15,6 +20,9 @@ impl Default for FormConfig {
             font_size_label: 18.0,
             font_size_description: 16.0,
             font_size_text: 15.0,
+            inline_descriptions: false,
+            omit_hidden_on_export: false,
+            omit_defaults_on_export: false,
         }
     }
 }
@@ -1,25 +1,376 @@
-use egui::{FontId, TextureOptions, Ui};
-use image::imageops::FilterType;
+use std::{
+    cell::RefCell,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
+
+use egui::{FontId, Ui};
 use indexmap::IndexMap;
 use toml::Value;
 
 use crate::editor::{
-    components::form_config::FormConfig,
-    form::form::{ArrayValue, Document, EnumField, Field, FieldType, FieldValue},
+    components::{
+        color_edit,
+        form_config::FormConfig,
+        raw_view::RawView,
+        reorder::Reorder,
+        rich_text,
+        sections::Sections,
+        texture_cache::TextureCache,
+        value_editor::ValueEditor,
+        vector_edit,
+        widgets::{WidgetContext, WidgetRegistry},
+    },
+    form::{
+        color::ColorValue,
+        compute::{self, Computations, ComputedState},
+        conditions::Conditions,
+        convert,
+        diff::{self, FieldChange},
+        expr,
+        form::{
+            ArrayValue, Document, DocumentError, EnumField, Field, FieldType, FieldValue, join_path,
+        },
+        formatting,
+        options::{OptionSets, ProjectOptions},
+        search, vector,
+    },
+    history::{Change, FileChange},
 };
 
 pub struct Form {
     document: Document,
+    /// The document as last read from or written to its file, to tell unsaved edits.
+    saved: Document,
+    /// File the document was opened from, if any.
+    path: Option<PathBuf>,
+    /// Option sets of the document's enums, resolved against the project.
+    options: OptionSets,
+    /// TOML text shown next to the form, if enabled.
+    raw: Option<RawView>,
+    /// Previews of the document's image fields.
+    textures: RefCell<TextureCache>,
+    /// Path of the field last focused or clicked, whose help is shown.
+    help_field: Option<String>,
+    /// Tables that are expanded.
+    sections: Sections,
+    /// Field to scroll to on the next frame, chosen in the outline or the find bar.
+    jump_to: Option<String>,
+    /// Find bar, while it is open.
+    search: Option<Search>,
 }
 
 impl Form {
     pub fn new(document: Document) -> Self {
-        Self { document }
+        Self {
+            options: ProjectOptions::default().resolve(&document),
+            raw: None,
+            textures: RefCell::default(),
+            help_field: None,
+            sections: Sections::default(),
+            jump_to: None,
+            search: None,
+            saved: document.clone(),
+            document,
+            path: None,
+        }
+    }
+
+    pub fn open(document: Document, path: PathBuf) -> Self {
+        Self {
+            options: ProjectOptions::default().resolve(&document),
+            raw: None,
+            textures: RefCell::default(),
+            help_field: None,
+            sections: Sections::default(),
+            jump_to: None,
+            search: None,
+            saved: document.clone(),
+            document,
+            path: Some(path),
+        }
+    }
+
+    /// Re-resolves the `options_from` sets, e.g. after project documents changed.
+    pub fn refresh_options(&mut self, project: &ProjectOptions) {
+        self.options = project.resolve(&self.document);
+    }
+
+    pub fn document(&self) -> &Document {
+        &self.document
+    }
+
+    /// Replaces the document, e.g. with its file as rewritten by a batch edit or an undo.
+    pub fn replace_document(&mut self, document: Document) {
+        self.saved = document.clone();
+        self.document = document;
+    }
+
+    /// Whether the document has edits that are not saved to its file.
+    pub fn is_dirty(&self) -> bool {
+        !diff::diff(&self.saved, &self.document).is_empty()
     }
 
-    pub fn show_state(&mut self, ui: &mut Ui, form_config: &FormConfig) {
+    /// Undoes one difference from `base`, as listed by [`diff::diff`].
+    pub fn revert_change(&mut self, base: &Document, change: &FieldChange) {
+        diff::revert(&mut self.document, base, change);
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Writes the document back to the file it was opened from, returning the write as
+    /// an undoable change.
+    ///
+    /// # Errors
+    /// Fails if the document has no file or cannot be written.
+    pub fn save(&mut self) -> Result<Change, DocumentError> {
+        let Some(path) = &self.path else {
+            return Err(DocumentError::IoError(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "document has no file",
+            )));
+        };
+        self.document.save(path)?;
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let before = std::mem::replace(&mut self.saved, self.document.clone());
+        Ok(Change {
+            label: format!("Save {name}"),
+            files: vec![FileChange {
+                path: path.clone(),
+                before,
+                after: self.document.clone(),
+            }],
+        })
+    }
+
+    /// The field last focused or clicked, with its path, to show help for.
+    pub fn help_field(&self) -> Option<(&str, &Field)> {
+        let path = self.help_field.as_deref()?;
+        Some((path, self.document.get(path)?))
+    }
+
+    /// Sets the field at `path` to `value`, e.g. an example from its help.
+    ///
+    /// # Errors
+    /// Fails if there is no such field or `value` does not fit its type.
+    pub fn set_field_value(&mut self, path: &str, value: toml::Value) -> Result<(), String> {
+        self.document
+            .get_mut(path)
+            .ok_or_else(|| format!("no field {path}"))?
+            .set_value_toml(value)
+    }
+
+    pub fn sections(&self) -> &Sections {
+        &self.sections
+    }
+
+    /// Restores which tables are expanded, e.g. from the last session.
+    pub fn set_sections(&mut self, sections: Sections) {
+        self.sections = sections;
+    }
+
+    pub fn expand_all(&mut self) {
+        self.sections.expand_all(&self.document);
+    }
+
+    pub fn collapse_all(&mut self) {
+        self.sections.collapse_all();
+    }
+
+    /// Bar of the document's top-level tables, each expanding and scrolling to its
+    /// table when clicked, with expand all / collapse all buttons.
+    pub fn show_outline(&mut self, ui: &mut Ui) {
+        ui.horizontal_wrapped(|ui| {
+            if ui.small_button("⊞ Expand all").clicked() {
+                self.expand_all();
+            }
+            if ui.small_button("⊟ Collapse all").clicked() {
+                self.collapse_all();
+            }
+            ui.separator();
+            for (key, field) in &self.document.fields {
+                if !matches!(field.value, FieldValue::Table { .. }) {
+                    continue;
+                }
+                let label = field.label.as_deref().unwrap_or(key);
+                let expanded = self.sections.is_expanded(key);
+                if ui.selectable_label(expanded, label).clicked() {
+                    self.sections.set_expanded(key, true);
+                    self.jump_to = Some(key.clone());
+                }
+            }
+        });
+    }
+
+    /// Opens the find bar, or focuses it if it is already open.
+    pub fn open_search(&mut self) {
+        self.search.get_or_insert_with(Search::default).focus = true;
+    }
+
+    pub fn is_searching(&self) -> bool {
+        self.search.is_some()
+    }
+
+    /// Find bar matching the key, label, description or value of fields. Enter and
+    /// Shift+Enter go to the next and previous match, Escape closes it.
+    pub fn show_search_bar(&mut self, ui: &mut Ui) {
+        let Some(search) = &mut self.search else {
+            return;
+        };
+        let conditions = Conditions::evaluate(&self.document);
+        search.matches = search::find(&self.document, &search.query)
+            .into_iter()
+            // Fields inside array items have no state of their own; their array's counts.
+            .filter(|path| {
+                conditions.is_visible(path)
+                    && conditions.is_visible(path.split('[').next().unwrap_or(path))
+            })
+            .collect();
+
+        let mut step = None;
+        let mut close = false;
+        ui.horizontal(|ui| {
+            ui.label("🔍");
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut search.query)
+                    .hint_text("Find field")
+                    .desired_width(220.0),
+            );
+            if std::mem::take(&mut search.focus) {
+                response.request_focus();
+            }
+            if response.changed() {
+                search.current = None;
+                step = Some(0);
+            }
+            if response.lost_focus() {
+                if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    step = Some(if ui.input(|i| i.modifiers.shift) {
+                        -1
+                    } else {
+                        1
+                    });
+                    response.request_focus();
+                } else if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                    close = true;
+                }
+            }
+
+            let any = !search.matches.is_empty();
+            if ui.add_enabled(any, egui::Button::new("▲")).clicked() {
+                step = Some(-1);
+            }
+            if ui.add_enabled(any, egui::Button::new("▼")).clicked() {
+                step = Some(1);
+            }
+            let position = search.position();
+            let count = match (position, search.matches.len()) {
+                (_, 0) if search.query.trim().is_empty() => String::new(),
+                (_, 0) => "No matches".to_owned(),
+                (Some(i), total) => format!("{} / {total}", i + 1),
+                (None, total) => format!("{total} matches"),
+            };
+            ui.label(egui::RichText::new(count).weak());
+            if ui.small_button("✕").on_hover_text("Close (Esc)").clicked() {
+                close = true;
+            }
+        });
+
+        if close {
+            self.search = None;
+        } else if let Some(step) = step {
+            self.go_to_match(step);
+        }
+    }
+
+    /// Moves `step` matches from the current one, wrapping around, and expands and
+    /// scrolls to it. A step of 0 goes to the first match if there is no current one.
+    fn go_to_match(&mut self, step: isize) {
+        let Some(search) = &mut self.search else {
+            return;
+        };
+        let count = search.matches.len() as isize;
+        if count == 0 {
+            return;
+        }
+        let index = match search.position() {
+            Some(i) => (i as isize + step).rem_euclid(count),
+            None if step < 0 => count - 1,
+            None => 0,
+        };
+        let Some(path) = search.matches.get(index as usize).cloned() else {
+            return;
+        };
+        for ancestor in search::ancestors(&path) {
+            self.sections.set_expanded(ancestor, true);
+        }
+        search.current = Some(path.clone());
+        self.jump_to = Some(path);
+    }
+
+    pub fn shows_raw(&self) -> bool {
+        self.raw.is_some()
+    }
+
+    /// Shows or hides the TOML text next to the form.
+    pub fn set_show_raw(&mut self, show: bool) {
+        if show != self.raw.is_some() {
+            self.raw = show.then(|| RawView::new(&self.document));
+        }
+    }
+
+    pub fn show_state(&mut self, ui: &mut Ui, form_config: &FormConfig, widgets: &WidgetRegistry) {
+        if self.raw.is_none() {
+            self.show_form(ui, form_config, widgets);
+            return;
+        }
+
+        ui.columns(2, |columns| {
+            let [form_ui, raw_ui] = columns else {
+                return;
+            };
+            let locked = self.raw.as_ref().is_some_and(RawView::has_error);
+            form_ui.add_enabled_ui(!locked, |ui| self.show_form(ui, form_config, widgets));
+
+            let Some(raw) = &mut self.raw else {
+                return;
+            };
+            raw.sync_from(&self.document);
+            let font = FontId::monospace(form_config.font_size_text);
+            if let Some(document) = raw.show(raw_ui, &font) {
+                self.document = document;
+            }
+        });
+    }
+
+    fn show_form(&mut self, ui: &mut Ui, form_config: &FormConfig, widgets: &WidgetRegistry) {
+        let computed = Computations::apply(&mut self.document);
+        let conditions = Conditions::evaluate(&self.document);
+        let help_field = RefCell::new(None);
+        let sections = RefCell::new(std::mem::take(&mut self.sections));
+        // Kept until the field is drawn, which takes a frame inside a table just expanded.
+        let jump_to = RefCell::new(self.jump_to.take());
+        let ctx = FieldContext {
+            form_config,
+            conditions: &conditions,
+            computed: &computed,
+            options: &self.options,
+            textures: &self.textures,
+            widgets,
+            help_field: &help_field,
+            sections: &sections,
+            jump_to: &jump_to,
+            search: self.search.as_ref(),
+        };
         for (key, field) in self.document.fields.iter_mut() {
-            Self::show_fields(field, ui, key, 0, form_config);
+            Self::show_fields(field, ui, key, key, 0, &ctx);
+        }
+        self.sections = sections.into_inner();
+        self.jump_to = jump_to.into_inner();
+        if let Some(path) = help_field.into_inner() {
+            self.help_field = Some(path);
         }
     }
 
@@ -31,96 +382,417 @@ impl Form {
         field: &mut Field,
         ui: &mut Ui,
         key: &str,
+        path: &str,
         level: usize,
-        form_config: &FormConfig,
+        ctx: &FieldContext<'_>,
     ) {
-        let fonts = FormFonts::from_config(form_config);
+        if !ctx.conditions.is_visible(path) {
+            return;
+        }
+
+        let fonts = FormFonts::from_config(ctx.form_config);
         let editable = field.editable.unwrap_or(false);
         let template = field.template.unwrap_or(false);
         let indent = 8.0 * level as f32;
+        let computed = ctx.computed.get(path);
+        let enabled = ctx.conditions.is_enabled(path) && !computed.is_some_and(|c| c.readonly);
 
-        match &mut field.value {
-            FieldValue::Array { value } => {
-                let label = field.label.clone();
-                let description = field.description.clone();
-
-                Self::show_array(
-                    ui,
-                    value,
-                    key,
-                    label.as_deref(),
-                    description.as_deref(),
-                    fonts,
-                    editable,
-                    template,
+        if field.is_optional() {
+            Self::render_unset_toggle(ui, field, indent, enabled);
+        }
+        let unset = field.is_unset();
+
+        let widget = ctx.widgets.find(field);
+        let response = ui.add_enabled_ui(enabled && !unset, |ui| {
+            if let Some(widget) = widget {
+                let label = field.label.clone().unwrap_or_else(|| key.to_owned());
+                let widget_ctx = WidgetContext {
+                    path,
+                    label: &label,
                     indent,
-                    level,
-                    form_config,
-                );
+                    label_font: fonts.label.clone(),
+                    text_font: fonts.text.clone(),
+                    description_font: fonts.description.clone(),
+                    inline_description: ctx.form_config.inline_descriptions,
+                    options: ctx.options,
+                };
+                widget.show(ui, field, &widget_ctx);
+                return;
+            }
+
+            let node = Node {
+                key,
+                path,
+                label: field.label.as_deref(),
+                description: field.description.as_deref(),
+                level,
+                editable,
+                template,
+            };
+            match &mut field.value {
+                FieldValue::Array { value } => {
+                    let description = node
+                        .description
+                        .filter(|_| ctx.form_config.inline_descriptions);
+                    Self::show_array(
+                        ui,
+                        value,
+                        &Node {
+                            description,
+                            ..node
+                        },
+                        ctx,
+                    );
+                }
+
+                FieldValue::Table { children } => {
+                    Self::render_table(ui, children, &node, ctx);
+                }
+
+                FieldValue::Boolean { .. }
+                | FieldValue::Float { .. }
+                | FieldValue::Integer { .. }
+                | FieldValue::String { .. }
+                | FieldValue::Image { .. }
+                | FieldValue::Enum { .. }
+                | FieldValue::Color { .. }
+                | FieldValue::Vec2 { .. }
+                | FieldValue::Vec3 { .. }
+                | FieldValue::Aabb { .. } => {
+                    Self::show_field_value(field, ui, key, path, level, ctx, fonts);
+                }
             }
+        });
+
+        Self::track_field_rect(ui, field, response.response.rect, path, ctx);
 
-            FieldValue::Boolean { .. }
-            | FieldValue::Float { .. }
-            | FieldValue::Integer { .. }
-            | FieldValue::String { .. }
-            | FieldValue::Image { .. }
-            | FieldValue::Enum { .. }
-            | FieldValue::Table { .. } => {
-                Self::show_field_value(field, ui, key, level, editable, template, fonts);
+        if !unset && field.value.is_default() == Some(false) {
+            Self::render_default_marker(ui, field, indent, enabled);
+        }
+        if enabled && field.convert.is_some() {
+            Self::render_convert_menu(ui, field, path, &response.response);
+        }
+        if field.compute.is_some() {
+            let fonts = FormFonts::from_config(ctx.form_config);
+            Self::render_compute_controls(ui, field, computed, indent, &fonts);
+        }
+        if let Some(error) = ctx.conditions.error(path) {
+            ui.colored_label(ui.visuals().warn_fg_color, error);
+        }
+    }
+
+    /// Hooks up the area a field was drawn in: its description tooltip, focus
+    /// tracking, and the find bar's highlight and scrolling.
+    fn track_field_rect(
+        ui: &Ui,
+        field: &Field,
+        rect: egui::Rect,
+        path: &str,
+        ctx: &FieldContext<'_>,
+    ) {
+        let is_table = matches!(field.value, FieldValue::Table { .. });
+        if !ctx.form_config.inline_descriptions && !is_table {
+            if let Some(description) = &field.description {
+                // Registered after the field's widgets, so it is hovered above them.
+                ui.interact(
+                    rect,
+                    ui.id().with(("field_tooltip", path)),
+                    egui::Sense::hover(),
+                )
+                .on_hover_text(description);
             }
+        }
+        Self::track_focus(ui, rect, path, ctx);
+        // Tables mark and scroll to their header instead.
+        if !is_table {
+            Self::highlight_match(ui, rect, path, ctx);
+            if ctx.take_jump(path) {
+                ui.scroll_to_rect(rect, Some(egui::Align::Center));
+            }
+        }
+    }
 
-            _ => {}
+    /// Remembers `path` as the field to show help for if one of its widgets has focus or
+    /// was just clicked. Nested fields are shown before their parents, so the innermost
+    /// field wins.
+    fn track_focus(ui: &Ui, rect: egui::Rect, path: &str, ctx: &FieldContext<'_>) {
+        let focused = ui
+            .memory(|m| m.focused())
+            .and_then(|id| ui.ctx().read_response(id))
+            .is_some_and(|focused| rect.contains_rect(focused.rect));
+        let clicked = ui.rect_contains_pointer(rect) && ui.input(|i| i.pointer.any_pressed());
+        let mut help_field = ctx.help_field.borrow_mut();
+        if (focused || clicked) && help_field.is_none() {
+            *help_field = Some(path.to_owned());
         }
     }
 
+    /// Outlines `rect` if the field at `path` matches the find bar, more strongly for the
+    /// current match.
+    fn highlight_match(ui: &Ui, rect: egui::Rect, path: &str, ctx: &FieldContext<'_>) {
+        let Some(search) = ctx.search else {
+            return;
+        };
+        if !search.matches.iter().any(|m| m == path) {
+            return;
+        }
+        let current = search.current.as_deref() == Some(path);
+        let color = ui.visuals().selection.stroke.color;
+        let stroke = egui::Stroke::new(if current { 2.0 } else { 1.0 }, color);
+        if current {
+            ui.painter().rect_filled(
+                rect.expand(2.0),
+                3.0,
+                ui.visuals().selection.bg_fill.gamma_multiply(0.25),
+            );
+        }
+        ui.painter()
+            .rect_stroke(rect.expand(2.0), 3.0, stroke, egui::StrokeKind::Outside);
+    }
+
+    /// Marks a value that differs from its schema default, with a button resetting it.
+    fn render_default_marker(ui: &mut Ui, field: &mut Field, indent: f32, enabled: bool) {
+        ui.horizontal(|ui| {
+            ui.add_space(indent);
+            let default = field.default_text().unwrap_or_default();
+            ui.label(
+                egui::RichText::new(format!("● differs from the default {default}"))
+                    .small()
+                    .color(ui.visuals().warn_fg_color),
+            );
+            if ui
+                .add_enabled(enabled, egui::Button::new("↺ Reset").small())
+                .clicked()
+            {
+                field.value.reset();
+            }
+        });
+    }
+
+    /// Checkbox setting or unsetting an optional field, with the game default shown
+    /// while it is unset.
+    fn render_unset_toggle(ui: &mut Ui, field: &mut Field, indent: f32, enabled: bool) {
+        ui.horizontal(|ui| {
+            ui.add_space(indent);
+            let mut set = !field.is_unset();
+            let toggle = ui
+                .add_enabled(enabled, egui::Checkbox::new(&mut set, "Set"))
+                .on_hover_text(
+                    "Unset fields are left out of exports, so the game uses its default",
+                );
+            if toggle.changed() {
+                field.set_unset(!set);
+            }
+            if !set {
+                let text = match field.default_text() {
+                    Some(default) => format!("unset, the game uses its default ({default})"),
+                    None => "unset, the game uses its default".to_owned(),
+                };
+                ui.label(egui::RichText::new(text).weak().italics());
+            }
+        });
+    }
+
+    /// Right-click menu converting the field to the types its schema allows. Lossy
+    /// conversions ask for confirmation.
+    fn render_convert_menu(ui: &Ui, field: &mut Field, path: &str, response: &egui::Response) {
+        let opened = response.contains_pointer() && ui.input(|i| i.pointer.secondary_clicked());
+        let mut converted = None;
+        egui::Popup::menu(response)
+            .id(ui.make_persistent_id(("convert_menu", path)))
+            .open_memory(opened.then_some(egui::SetOpenCommand::Bool(true)))
+            .at_pointer_fixed()
+            .show(|ui| {
+                ui.label(egui::RichText::new("Convert to").weak());
+                for conversion in convert::conversions(field) {
+                    let name = format!("{:?}", conversion.to);
+                    match conversion.field {
+                        Err(reason) => {
+                            ui.add_enabled(false, egui::Button::new(name))
+                                .on_disabled_hover_text(reason);
+                        }
+                        Ok(new_field) if conversion.lossless => {
+                            if ui
+                                .button(name)
+                                .on_hover_text(&conversion.description)
+                                .clicked()
+                            {
+                                converted = Some(new_field);
+                                ui.close();
+                            }
+                        }
+                        Ok(new_field) => {
+                            ui.menu_button(format!("{name} ⚠"), |ui| {
+                                ui.label(format!("{} loses information.", conversion.description));
+                                if ui.button("Convert anyway").clicked() {
+                                    converted = Some(new_field);
+                                    ui.close();
+                                }
+                            });
+                        }
+                    }
+                }
+            });
+        if let Some(new_field) = converted {
+            *field = new_field;
+        }
+    }
+
+    /// Formula hint, "use suggestion" and detach / reattach buttons of a computed field.
+    fn render_compute_controls(
+        ui: &mut Ui,
+        field: &mut Field,
+        computed: Option<&ComputedState>,
+        indent: f32,
+        fonts: &FormFonts,
+    ) {
+        ui.horizontal(|ui| {
+            ui.add_space(indent);
+            let Some(state) = computed else {
+                ui.label(
+                    egui::RichText::new("Detached from formula")
+                        .font(fonts.description.clone())
+                        .weak(),
+                );
+                if ui.small_button("Reattach").clicked() {
+                    field.detached = None;
+                }
+                return;
+            };
+
+            ui.label(
+                egui::RichText::new(format!("ƒ {}", state.formula))
+                    .font(fonts.description.clone())
+                    .weak(),
+            );
+            if let Some(error) = &state.error {
+                ui.colored_label(ui.visuals().warn_fg_color, error);
+            }
+            if let (false, Some(suggestion)) = (state.readonly, &state.suggestion) {
+                if !compute::holds(field, suggestion)
+                    && ui
+                        .small_button(format!("Use {}", suggestion.to_text()))
+                        .clicked()
+                {
+                    compute::assign(&mut field.value, suggestion);
+                }
+            }
+            if ui
+                .small_button("Detach")
+                .on_hover_text("Stop computing this field and edit it by hand")
+                .clicked()
+            {
+                field.detached = Some(true);
+            }
+        });
+    }
+
     fn show_field_value(
         field: &mut Field,
         ui: &mut Ui,
         key: &str,
+        path: &str,
         level: usize,
-        editable: bool,
-        template: bool,
+        ctx: &FieldContext<'_>,
         fonts: FormFonts,
     ) {
         let indent = 8.0 * level as f32;
         let label = field.label.as_deref().unwrap_or(key);
+        // Without inline descriptions, the description is the field's tooltip instead.
+        let description = if ctx.form_config.inline_descriptions {
+            field.description.clone()
+        } else {
+            None
+        };
 
         match &mut field.value {
             FieldValue::Boolean { value, .. } => {
-                Self::render_boolean(ui, value, label, &field.description, fonts);
+                Self::render_boolean(ui, value, label, &description, fonts);
             }
 
             FieldValue::Float { value, .. } => {
-                Self::render_float(ui, value, label, &field.description, fonts);
+                Self::render_float(ui, value, label, &description, fonts);
             }
 
             FieldValue::Integer { value, .. } => {
-                Self::render_integer(ui, value, label, &field.description, fonts);
+                Self::render_integer(ui, value, label, &description, fonts);
             }
 
-            FieldValue::String { value, default } => {
-                Self::render_string(ui, value, default, label, &field.description, indent, fonts);
+            FieldValue::String {
+                value,
+                default,
+                multiline,
+                max_length,
+                max_lines,
+            } => {
+                let limits = (*max_length, *max_lines);
+                if multiline.unwrap_or(false) {
+                    if value.is_empty() {
+                        if let Some(def) = default {
+                            *value = def.clone();
+                        }
+                    }
+                    Self::render_text(ui, value, label, &description, limits, indent, fonts);
+                } else {
+                    Self::render_string(ui, value, default, label, &description, indent, fonts);
+                    if let Some(err) = formatting::limit_error(value, limits.0, limits.1) {
+                        ui.colored_label(ui.visuals().error_fg_color, err);
+                    }
+                }
             }
 
-            FieldValue::Table { children } => {
-                Self::render_table(
-                    ui,
-                    children,
-                    label,
-                    &field.description,
-                    level,
-                    fonts,
-                    editable,
-                    template,
-                );
+            FieldValue::Image { value } => {
+                Self::render_image(ui, value, label, fonts, ctx.textures);
+            }
+
+            FieldValue::Enum {
+                value,
+                options,
+                options_from,
+                free_text,
+            } => {
+                let choices = EnumChoices {
+                    options: ctx.options.options_for(options_from.as_deref(), options),
+                    source: options_from.as_deref(),
+                    free_text: free_text.unwrap_or(false),
+                };
+                Self::render_enum(ui, value, &choices, label, &description, fonts, indent);
+            }
+
+            FieldValue::Color { value, alpha, .. } => {
+                // Scoped to the field, so fields with the same label keep their own state.
+                ui.push_id(("color_field", path), |ui| {
+                    Self::render_color(
+                        ui,
+                        value,
+                        alpha.unwrap_or(false),
+                        label,
+                        &description,
+                        fonts,
+                        indent,
+                    );
+                });
+            }
+
+            FieldValue::Vec2 {
+                value, min, max, ..
+            } => {
+                let range = vector::range(*min, *max);
+                Self::render_vector(ui, value, &range, label, &description, fonts);
             }
 
-            FieldValue::Image { value, texture } => {
-                Self::render_image(ui, value, texture, label, fonts);
+            FieldValue::Vec3 {
+                value, min, max, ..
+            } => {
+                let range = vector::range(*min, *max);
+                Self::render_vector(ui, value, &range, label, &description, fonts);
             }
 
-            FieldValue::Enum { value, options } => {
-                Self::render_enum(ui, value, options, label, &field.description, fonts, indent);
+            FieldValue::Aabb {
+                value, min, max, ..
+            } => {
+                let range = vector::range(*min, *max);
+                Self::render_aabb(ui, value, &range, label, &description, fonts);
             }
 
             _ => {}
@@ -134,7 +806,7 @@ impl Form {
     fn render_enum(
         ui: &mut Ui,
         value: &mut String,
-        options: &mut Vec<String>,
+        choices: &EnumChoices<'_>,
         label: &str,
         description: &Option<String>,
         fonts: FormFonts,
@@ -145,27 +817,51 @@ impl Form {
             ui.add_space(indent);
             ui.label(egui::RichText::new(label).font(fonts.label));
 
-            if !options.is_empty() && !options.contains(value) {
-                *value = options[0].clone();
-            }
-
             let combo_id = ui.make_persistent_id(format!("enum_combo_{}", label));
-
-            egui::ComboBox::from_id_salt(combo_id)
-                .selected_text(if value.is_empty() {
-                    "Select..."
-                } else {
-                    value.as_str()
-                })
-                .show_ui(ui, |ui| {
-                    for option in options.iter() {
-                        ui.selectable_value(value, option.clone(), option.as_str());
-                    }
-                });
+            Self::render_enum_selector(ui, combo_id, value, choices);
         });
         Self::render_description(ui, description, &fonts.description);
         ui.add_space(4.0);
     }
+
+    /// Combo box of an enum value, preceded by a text field when free text is allowed.
+    fn render_enum_selector(
+        ui: &mut Ui,
+        combo_id: egui::Id,
+        value: &mut String,
+        choices: &EnumChoices<'_>,
+    ) {
+        // A fixed list snaps to a valid option. A referenced set may change with the
+        // project, so values missing from it are only flagged.
+        if choices.source.is_none() && !choices.free_text && !choices.options.contains(value) {
+            if let Some(first) = choices.options.first() {
+                value.clone_from(first);
+            }
+        }
+
+        if choices.free_text {
+            ui.add(egui::TextEdit::singleline(value).desired_width(200.0));
+        }
+        let selected_text = match (choices.free_text, value.is_empty()) {
+            (true, _) => "",
+            (false, true) => "Select...",
+            (false, false) => value.as_str(),
+        };
+        egui::ComboBox::from_id_salt(combo_id)
+            .selected_text(selected_text.to_owned())
+            .show_ui(ui, |ui| {
+                for option in &choices.options {
+                    ui.selectable_value(value, option.clone(), option.as_str());
+                }
+            });
+
+        if let (Some(source), false) = (choices.source, choices.free_text) {
+            if !value.is_empty() && !choices.options.contains(value) {
+                ui.colored_label(ui.visuals().warn_fg_color, "⚠")
+                    .on_hover_text(format!("Not in the `{source}` options"));
+            }
+        }
+    }
     fn render_boolean(
         ui: &mut Ui,
         value: &mut bool,
@@ -184,6 +880,81 @@ impl Form {
         ui.add_space(4.0);
     }
 
+    fn render_color(
+        ui: &mut Ui,
+        value: &mut ColorValue,
+        alpha: bool,
+        label: &str,
+        description: &Option<String>,
+        fonts: FormFonts,
+        indent: f32,
+    ) {
+        ui.add_space(4.0);
+        ui.horizontal(|ui| {
+            ui.add_space(indent);
+            ui.label(egui::RichText::new(label).font(fonts.label));
+            let id = ui.make_persistent_id("color_edit");
+            color_edit::color_edit(ui, id, value, alpha, &fonts.text);
+        });
+        Self::render_description(ui, description, &fonts.description);
+        ui.add_space(4.0);
+    }
+
+    fn render_vector(
+        ui: &mut Ui,
+        values: &mut [f64],
+        range: &RangeInclusive<f64>,
+        label: &str,
+        description: &Option<String>,
+        fonts: FormFonts,
+    ) {
+        ui.add_space(4.0);
+        ui.vertical(|ui| {
+            ui.label(egui::RichText::new(label).font(fonts.label));
+            ui.add_space(2.0);
+            ui.horizontal(|ui| {
+                vector_edit::vector_edit(ui, values, &vector::AXES, range);
+            });
+            Self::render_description(ui, description, &fonts.description);
+        });
+        ui.add_space(4.0);
+    }
+
+    /// Min and max corner of the box, with a top and side wireframe next to them.
+    fn render_aabb(
+        ui: &mut Ui,
+        value: &mut [f64; 6],
+        range: &RangeInclusive<f64>,
+        label: &str,
+        description: &Option<String>,
+        fonts: FormFonts,
+    ) {
+        ui.add_space(4.0);
+        ui.vertical(|ui| {
+            ui.label(egui::RichText::new(label).font(fonts.label));
+            ui.add_space(2.0);
+            ui.horizontal(|ui| {
+                ui.vertical(|ui| {
+                    let (min, max) = value.split_at_mut(3);
+                    ui.horizontal(|ui| {
+                        ui.label("min");
+                        vector_edit::vector_edit(ui, min, &vector::AXES, range);
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("max");
+                        vector_edit::vector_edit(ui, max, &vector::AXES, range);
+                    });
+                });
+                vector_edit::aabb_preview(ui, value, range);
+            });
+            if let Some(err) = vector::aabb_error(value, range) {
+                ui.colored_label(ui.visuals().error_fg_color, err);
+            }
+            Self::render_description(ui, description, &fonts.description);
+        });
+        ui.add_space(4.0);
+    }
+
     fn render_float(
         ui: &mut Ui,
         value: &mut f64,
@@ -247,139 +1018,239 @@ impl Form {
         ui.add_space(4.0);
     }
 
-    fn render_table(
+    /// Text area with highlighted `§` codes, a character count and an in-game preview.
+    fn render_text(
         ui: &mut Ui,
-        children: &mut IndexMap<String, Field>,
+        value: &mut String,
         label: &str,
         description: &Option<String>,
-        level: usize,
+        (max_length, max_lines): (Option<usize>, Option<usize>),
+        indent: f32,
         fonts: FormFonts,
-        editable: bool,
-        template: bool,
     ) {
+        let error = formatting::limit_error(value, max_length, max_lines);
         ui.add_space(4.0);
-        ui.collapsing(
-            egui::RichText::new(label)
-                .font(fonts.label.clone())
-                .strong(),
-            |ui| {
-                if let Some(desc) = description.as_deref() {
-                    ui.add_space(1.0);
-                    ui.label(
-                        egui::RichText::new(desc)
-                            .font(fonts.description.clone())
-                            .color(egui::Color32::GRAY),
-                    );
-                    ui.separator();
+        ui.horizontal(|ui| {
+            ui.add_space(indent);
+            ui.label(egui::RichText::new(label).font(fonts.label));
+            let length = formatting::visible_len(value);
+            let count = match max_length {
+                Some(max) => format!("{length}/{max}"),
+                None => length.to_string(),
+            };
+            let count = egui::RichText::new(count).font(fonts.description.clone());
+            if error.is_some() {
+                ui.colored_label(ui.visuals().error_fg_color, count);
+            } else {
+                ui.label(count.weak());
+            }
+        });
+
+        let font = fonts.text.clone();
+        let mut layouter = |ui: &Ui, text: &dyn egui::TextBuffer, wrap_width: f32| {
+            let mut job = rich_text::highlight(ui, text.as_str(), &font);
+            job.wrap.max_width = wrap_width;
+            ui.fonts_mut(|f| f.layout_job(job))
+        };
+        ui.horizontal(|ui| {
+            ui.add_space(indent);
+            ui.vertical(|ui| {
+                ui.add(
+                    egui::TextEdit::multiline(value)
+                        .font(fonts.text.clone())
+                        .desired_rows(max_lines.unwrap_or(4).clamp(2, 8))
+                        .desired_width(f32::INFINITY)
+                        .layouter(&mut layouter),
+                );
+                if value.contains(formatting::SECTION) {
+                    rich_text::preview(ui, value, &fonts.text);
                 }
-                let mut remove_key: Option<String> = None;
-                for (child_key, child_field) in children.iter_mut() {
-                    ui.horizontal(|ui| {
-                        Self::show_fields(
-                            child_field,
-                            ui,
-                            child_key,
-                            level + 1,
-                            &FormConfig::default(),
-                        );
-                        if editable {
-                            if ui
-                                .add(
-                                    egui::Button::new("✕")
-                                        .fill(egui::Color32::TRANSPARENT)
-                                        .stroke(egui::Stroke::NONE),
-                                )
-                                .clicked()
-                            {
-                                remove_key = Some(child_key.to_string());
-                            }
-                        }
-                    });
+                if let Some(err) = error {
+                    ui.colored_label(ui.visuals().error_fg_color, err);
                 }
-                if let Some(key) = remove_key {
-                    children.shift_remove(&key);
+            });
+        });
+        Self::render_description(ui, description, &fonts.description);
+        ui.add_space(4.0);
+    }
+
+    fn render_table(
+        ui: &mut Ui,
+        children: &mut IndexMap<String, Field>,
+        node: &Node<'_>,
+        ctx: &FieldContext<'_>,
+    ) {
+        let fonts = FormFonts::from_config(ctx.form_config);
+        let (path, editable) = (node.path, node.editable);
+        ui.add_space(4.0);
+        // Keyed by path, so tables sharing a label keep their own state.
+        let expanded = ctx.sections.borrow().is_expanded(path);
+        let header = egui::CollapsingHeader::new(
+            egui::RichText::new(node.label.unwrap_or(node.key))
+                .font(fonts.label.clone())
+                .strong(),
+        )
+        .id_salt(("section", path))
+        .open(Some(expanded))
+        .show(ui, |ui| {
+            if let Some(desc) = node.description {
+                ui.add_space(1.0);
+                ui.label(
+                    egui::RichText::new(desc)
+                        .font(fonts.description.clone())
+                        .color(egui::Color32::GRAY),
+                );
+                ui.separator();
+            }
+            if children.values().any(Field::differs_from_default)
+                && ui
+                    .small_button("↺ Reset section")
+                    .on_hover_text("Reset every field of the section to its default")
+                    .clicked()
+            {
+                for child in children.values_mut() {
+                    child.reset();
                 }
-                if editable {
-                    ui.separator();
-                    ui.horizontal(|ui| {
-                        ui.label("Add field:");
-                        let combo_id = ui.make_persistent_id(format!("field_type_combo_{}", label));
-                        let text_id = ui.make_persistent_id(format!("new_key_text_{}", label));
-
-                        let mut selected = ui.data_mut(|d| {
-                            d.get_persisted::<FieldType>(combo_id)
-                                .unwrap_or(FieldType::String)
-                        });
-
-                        if !template {
-                            egui::ComboBox::from_id_salt(combo_id)
-                                .selected_text(format!("{:?}", selected))
-                                .show_ui(ui, |ui| {
-                                    ui.selectable_value(&mut selected, FieldType::String, "String");
-                                    ui.selectable_value(
-                                        &mut selected,
-                                        FieldType::Integer,
-                                        "Integer",
-                                    );
-                                    ui.selectable_value(&mut selected, FieldType::Float, "Float");
-                                    ui.selectable_value(
-                                        &mut selected,
-                                        FieldType::Boolean,
-                                        "Boolean",
-                                    );
-                                    ui.selectable_value(&mut selected, FieldType::Image, "Image");
-                                    ui.selectable_value(&mut selected, FieldType::Table, "Table");
-                                    ui.selectable_value(&mut selected, FieldType::Array, "Array");
-                                });
-                            ui.data_mut(|d| d.insert_persisted(combo_id, selected));
-                        }
+            }
+            let mut remove_key: Option<String> = None;
+            let mut reorder = Reorder::new(ui.id().with("reorder"), children.len());
+            for (i, (child_key, child_field)) in children.iter_mut().enumerate() {
+                let row = ui.horizontal(|ui| {
+                    if editable {
+                        reorder.handle(ui, i);
+                    }
+                    Self::show_fields(
+                        child_field,
+                        ui,
+                        child_key,
+                        &join_path(path, child_key),
+                        node.level + 1,
+                        ctx,
+                    );
+                    if editable
+                        && ui
+                            .add(
+                                egui::Button::new("✕")
+                                    .fill(egui::Color32::TRANSPARENT)
+                                    .stroke(egui::Stroke::NONE),
+                            )
+                            .clicked()
+                    {
+                        remove_key = Some(child_key.clone());
+                    }
+                });
+                reorder.drop_target(ui, &row.response, i);
+            }
+            reorder.apply_map(ui, children);
+            if let Some(key) = remove_key {
+                children.shift_remove(&key);
+            }
+            if editable {
+                ui.separator();
+                Self::render_add_field(ui, children, path, node.template);
+            }
+        });
+        if header.header_response.clicked() {
+            ctx.sections.borrow_mut().set_expanded(path, !expanded);
+        }
+        Self::highlight_match(ui, header.header_response.rect, path, ctx);
+        if ctx.take_jump(path) {
+            header.header_response.scroll_to_me(Some(egui::Align::TOP));
+        }
+    }
 
-                        let mut new_key = ui.data_mut(|d| {
-                            d.get_persisted::<String>(text_id)
-                                .unwrap_or_else(|| String::from("value"))
-                        });
-
-                        ui.text_edit_singleline(&mut new_key);
-                        ui.data_mut(|d| d.insert_persisted(text_id, new_key.clone()));
-
-                        if ui.button("Add").clicked() {
-                            let mut new_field = if template {
-                                children
-                                    .get_index(0)
-                                    .map(|(_, f)| f.clone())
-                                    .unwrap_or_else(Field::default_string)
-                            } else {
-                                match selected {
-                                    FieldType::String => Field::default_string(),
-                                    FieldType::Integer => Field::default_integer(),
-                                    FieldType::Float => Field::default_float(),
-                                    FieldType::Boolean => Field::default_boolean(),
-                                    FieldType::Image => Field::default_image(),
-                                    FieldType::Table => Field::default_table(),
-                                    FieldType::Array => Field::default_array(),
-                                }
-                            };
-                            new_field.label = Some(new_key.clone());
-                            children.insert(new_key.clone(), new_field);
-                            ui.data_mut(|d| d.insert_persisted(text_id, String::from("value")));
-                        }
+    /// The row adding a field to an editable table. Template tables add a copy of
+    /// their first field instead of one of the picked type.
+    fn render_add_field(
+        ui: &mut Ui,
+        children: &mut IndexMap<String, Field>,
+        path: &str,
+        template: bool,
+    ) {
+        ui.horizontal(|ui| {
+            ui.label("Add field:");
+            let combo_id = ui.make_persistent_id(("field_type_combo", path));
+            let text_id = ui.make_persistent_id(("new_key_text", path));
+
+            let mut selected = ui.data_mut(|d| {
+                d.get_persisted::<FieldType>(combo_id)
+                    .unwrap_or(FieldType::String)
+            });
+
+            if !template {
+                egui::ComboBox::from_id_salt(combo_id)
+                    .selected_text(format!("{selected:?}"))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut selected, FieldType::String, "String");
+                        ui.selectable_value(&mut selected, FieldType::Integer, "Integer");
+                        ui.selectable_value(&mut selected, FieldType::Float, "Float");
+                        ui.selectable_value(&mut selected, FieldType::Boolean, "Boolean");
+                        ui.selectable_value(&mut selected, FieldType::Image, "Image");
+                        ui.selectable_value(&mut selected, FieldType::Table, "Table");
+                        ui.selectable_value(&mut selected, FieldType::Array, "Array");
+                        ui.selectable_value(&mut selected, FieldType::Enum, "Enum");
+                        ui.selectable_value(&mut selected, FieldType::Color, "Color");
+                        ui.selectable_value(&mut selected, FieldType::Vec2, "Vec2");
+                        ui.selectable_value(&mut selected, FieldType::Vec3, "Vec3");
+                        ui.selectable_value(&mut selected, FieldType::Aabb, "AABB");
                     });
-                }
-            },
-        );
+                ui.data_mut(|d| d.insert_persisted(combo_id, selected));
+            }
+
+            let mut new_key = ui.data_mut(|d| {
+                d.get_persisted::<String>(text_id)
+                    .unwrap_or_else(|| String::from("value"))
+            });
+
+            ui.text_edit_singleline(&mut new_key);
+            ui.data_mut(|d| d.insert_persisted(text_id, new_key.clone()));
+
+            if ui.button("Add").clicked() {
+                let mut new_field = if template {
+                    children
+                        .get_index(0)
+                        .map(|(_, f)| f.clone())
+                        .unwrap_or_else(Field::default_string)
+                } else {
+                    match selected {
+                        FieldType::String => Field::default_string(),
+                        FieldType::Integer => Field::default_integer(),
+                        FieldType::Float => Field::default_float(),
+                        FieldType::Boolean => Field::default_boolean(),
+                        FieldType::Image => Field::default_image(),
+                        FieldType::Table => Field::default_table(),
+                        FieldType::Array => Field::default_array(),
+                        FieldType::Enum => Field::default_enum(),
+                        FieldType::Color => Field::default_color(),
+                        FieldType::Vec2 => Field::default_vec2(),
+                        FieldType::Vec3 => Field::default_vec3(),
+                        FieldType::Aabb => Field::default_aabb(),
+                    }
+                };
+                new_field.label = Some(new_key.clone());
+                children.insert(new_key.clone(), new_field);
+                ui.data_mut(|d| d.insert_persisted(text_id, String::from("value")));
+            }
+        });
     }
 
     fn render_image(
         ui: &mut Ui,
         value: &mut String,
-        texture: &mut Option<egui::TextureHandle>,
         label: &str,
         fonts: FormFonts,
+        textures: &RefCell<TextureCache>,
     ) {
         ui.vertical(|ui| {
             ui.horizontal(|ui| {
                 if ui.button("Add texture").clicked() {
-                    Self::handle_image_upload(ui, value, texture);
+                    if let Some(path) = rfd::FileDialog::new()
+                        .add_filter("Images", &["png"])
+                        .pick_file()
+                    {
+                        *value = path.to_string_lossy().to_string();
+                        textures.borrow_mut().forget(value);
+                    }
                 }
 
                 if !value.is_empty() {
@@ -387,47 +1258,13 @@ impl Form {
                 }
             });
 
-            if let Some(tex) = texture.as_ref() {
+            if let Some(tex) = textures.borrow_mut().get(ui.ctx(), value) {
                 ui.add_space(2.0);
                 ui.add(egui::Image::new(tex).max_width(128.0));
             }
         });
     }
 
-    fn handle_image_upload(
-        ui: &mut Ui,
-        value: &mut String,
-        texture: &mut Option<egui::TextureHandle>,
-    ) {
-        if let Some(path) = rfd::FileDialog::new()
-            .add_filter("Images", &["png"])
-            .pick_file()
-        {
-            *value = path.to_string_lossy().to_string();
-
-            if let Ok(img) = image::open(&path) {
-                let mut img = img.to_rgba8();
-                let (width, height) = (img.width(), img.height());
-                let min_size = 128;
-
-                if width < min_size || height < min_size {
-                    let scale_w = min_size.max(width);
-                    let scale_h = min_size.max(height);
-                    img = image::imageops::resize(&img, scale_w, scale_h, FilterType::Nearest);
-                }
-
-                let size = [img.width() as _, img.height() as _];
-                let pixels = img.into_raw();
-
-                *texture = Some(ui.ctx().load_texture(
-                    value.clone(),
-                    egui::ColorImage::from_rgba_unmultiplied(size, &pixels),
-                    TextureOptions::NEAREST,
-                ));
-            }
-        }
-    }
-
     fn render_description(ui: &mut Ui, description: &Option<String>, font: &FontId) {
         if let Some(desc) = description.as_deref() {
             ui.add_space(1.0);
@@ -443,23 +1280,21 @@ impl Form {
     // ARRAY RENDERING
     // ==============================================
 
-    fn show_array(
-        ui: &mut Ui,
-        value: &mut ArrayValue,
-        key: &str,
-        label: Option<&str>,
-        description: Option<&str>,
-        fonts: FormFonts,
-        editable: bool,
-        template: bool,
-        indent: f32,
-        level: usize,
-        form_config: &FormConfig,
-    ) {
+    fn show_array(ui: &mut Ui, value: &mut ArrayValue, node: &Node<'_>, ctx: &FieldContext<'_>) {
+        let fonts = FormFonts::from_config(ctx.form_config);
+        let Node {
+            key,
+            path,
+            editable,
+            template,
+            ..
+        } = *node;
+        let indent = node.indent();
+
         ui.add_space(4.0);
-        ui.label(egui::RichText::new(label.unwrap_or(key)).font(fonts.label.clone()));
+        ui.label(egui::RichText::new(node.label.unwrap_or(key)).font(fonts.label.clone()));
 
-        if let Some(desc) = description {
+        if let Some(desc) = node.description {
             ui.add_space(2.0);
             ui.label(
                 egui::RichText::new(desc)
@@ -479,14 +1314,14 @@ impl Form {
             ArrayValue::Integers { items } => {
                 Self::render_array_integers(ui, items, fonts, editable, indent);
             }
-            ArrayValue::Objects { items } => {
-                Self::render_array_objects(ui, items, level, form_config, fonts, editable, indent);
+            ArrayValue::Objects { .. } => {
+                Self::render_array_objects(ui, value, node, ctx);
             }
             ArrayValue::Mixed { items } => {
-                Self::render_array_mixed(ui, items, fonts, indent);
+                Self::render_array_mixed(ui, items, path, fonts, editable, indent);
             }
-            ArrayValue::Enums { items } => {
-                Self::render_array_enums(ui, items, fonts, editable, template, indent);
+            ArrayValue::Enums { .. } => {
+                Self::render_array_enums(ui, value, ctx, fonts, editable, template, indent);
             }
         });
 
@@ -502,9 +1337,13 @@ impl Form {
     ) {
         let mut remove_index: Option<usize> = None;
 
+        let mut reorder = Reorder::new(ui.id().with("reorder"), strings.len());
         for (i, s) in strings.iter_mut().enumerate() {
-            ui.horizontal(|ui| {
+            let row = ui.horizontal(|ui| {
                 ui.add_space(indent);
+                if editable {
+                    reorder.handle(ui, i);
+                }
                 ui.label(egui::RichText::new(format!("[{}]", i)).font(fonts.label.clone()));
                 ui.add(
                     egui::TextEdit::singleline(s)
@@ -515,7 +1354,9 @@ impl Form {
                     remove_index = Some(i);
                 }
             });
+            reorder.drop_target(ui, &row.response, i);
         }
+        reorder.apply(ui, strings);
 
         if let Some(i) = remove_index {
             strings.remove(i);
@@ -535,16 +1376,22 @@ impl Form {
     ) {
         let mut remove_index: Option<usize> = None;
 
+        let mut reorder = Reorder::new(ui.id().with("reorder"), numbers.len());
         for (i, n) in numbers.iter_mut().enumerate() {
-            ui.horizontal(|ui| {
+            let row = ui.horizontal(|ui| {
                 ui.add_space(indent);
+                if editable {
+                    reorder.handle(ui, i);
+                }
                 ui.label(egui::RichText::new(format!("[{}]", i)).font(fonts.label.clone()));
                 ui.add(egui::DragValue::new(n));
                 if editable && ui.button("X").clicked() {
                     remove_index = Some(i);
                 }
             });
+            reorder.drop_target(ui, &row.response, i);
         }
+        reorder.apply(ui, numbers);
 
         if let Some(i) = remove_index {
             numbers.remove(i);
@@ -564,16 +1411,22 @@ impl Form {
     ) {
         let mut remove_index: Option<usize> = None;
 
+        let mut reorder = Reorder::new(ui.id().with("reorder"), numbers.len());
         for (i, n) in numbers.iter_mut().enumerate() {
-            ui.horizontal(|ui| {
+            let row = ui.horizontal(|ui| {
                 ui.add_space(indent);
+                if editable {
+                    reorder.handle(ui, i);
+                }
                 ui.label(egui::RichText::new(format!("[{}]", i)).font(fonts.label.clone()));
                 ui.add(egui::DragValue::new(n));
                 if editable && ui.button("X").clicked() {
                     remove_index = Some(i);
                 }
             });
+            reorder.drop_target(ui, &row.response, i);
         }
+        reorder.apply(ui, numbers);
 
         if let Some(i) = remove_index {
             numbers.remove(i);
@@ -584,99 +1437,179 @@ impl Form {
         }
     }
 
+    /// Items of an object array, each under a collapsible header with reorder, duplicate
+    /// and remove buttons. New items are copies of the array's `item` template.
     fn render_array_objects(
         ui: &mut Ui,
-        objects: &mut Vec<IndexMap<String, Field>>,
-        level: usize,
-        form_config: &FormConfig,
-        fonts: FormFonts,
-        editable: bool,
-        indent: f32,
+        value: &mut ArrayValue,
+        node: &Node<'_>,
+        ctx: &FieldContext<'_>,
     ) {
-        let mut remove_index: Option<usize> = None;
-
-        for (i, obj) in objects.iter_mut().enumerate() {
-            ui.horizontal(|ui| {
+        let fonts = FormFonts::from_config(ctx.form_config);
+        let (path, editable) = (node.path, node.editable);
+        let indent = node.indent();
+        let ArrayValue::Objects {
+            item,
+            min_items,
+            max_items,
+            item_label,
+            items,
+        } = value
+        else {
+            return;
+        };
+        let can_add = max_items.is_none_or(|max| items.len() < max);
+        let can_remove = min_items.is_none_or(|min| items.len() > min);
+        let count = items.len();
+        let mut action = None;
+        let ids_key = ui.make_persistent_id(("object_item_ids", path));
+        let mut ids: ItemIds = ui.data_mut(|d| d.get_temp(ids_key)).unwrap_or_default();
+        ids.fit(count);
+
+        let mut reorder = Reorder::new(ui.make_persistent_id(("object_items", path)), count);
+        for (i, obj) in items.iter_mut().enumerate() {
+            let title = item_label
+                .as_deref()
+                .and_then(|key| obj.get(key))
+                .and_then(|field| expr::Value::from_field(&field.value))
+                .map(|value| value.to_text())
+                .filter(|text| !text.is_empty())
+                .unwrap_or_else(|| format!("Item {i}"));
+
+            let id = ui.make_persistent_id(("object_item", path, ids.get(i)));
+            let (_, header, _) = egui::collapsing_header::CollapsingState::load_with_default_open(
+                ui.ctx(),
+                id,
+                true,
+            )
+            .show_header(ui, |ui| {
                 ui.add_space(indent);
-                ui.label(egui::RichText::new(format!("Item {}", i)).font(fonts.label.clone()));
-                if editable && ui.button("X").clicked() {
-                    remove_index = Some(i);
+                if editable {
+                    reorder.handle(ui, i);
                 }
-            });
-
-            ui.indent(format!("item_{}", i), |ui| {
+                ui.label(egui::RichText::new(title).font(fonts.label.clone()));
+                if !editable {
+                    return;
+                }
+                if ui.add_enabled(i > 0, egui::Button::new("⬆")).clicked() {
+                    action = Some(ObjectAction::Swap(i, i - 1));
+                }
+                if ui
+                    .add_enabled(i + 1 < count, egui::Button::new("⬇"))
+                    .clicked()
+                {
+                    action = Some(ObjectAction::Swap(i, i + 1));
+                }
+                if ui
+                    .add_enabled(can_add, egui::Button::new("Duplicate"))
+                    .clicked()
+                {
+                    action = Some(ObjectAction::Duplicate(i));
+                }
+                if ui.add_enabled(can_remove, egui::Button::new("X")).clicked() {
+                    action = Some(ObjectAction::Remove(i));
+                }
+            })
+            .body(|ui| {
                 for (child_key, child_field) in obj.iter_mut() {
-                    Self::show_fields(child_field, ui, child_key, level + 1, form_config);
+                    let child_path = join_path(&format!("{path}[{i}]"), child_key);
+                    Self::show_fields(child_field, ui, child_key, &child_path, node.level + 1, ctx);
                 }
             });
+            reorder.drop_target(ui, &header.response, i);
         }
-
-        if let Some(i) = remove_index {
-            objects.remove(i);
+        reorder.apply(ui, items);
+        reorder.apply(ui, &mut ids.ids);
+
+        if let (true, Some(min)) = (editable, *min_items) {
+            if items.len() < min {
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    format!("At least {min} items are required"),
+                );
+            }
+        }
+        if editable {
+            // Without a template, new items copy the fields of the last one.
+            let has_source = item.is_some() || !items.is_empty();
+            let add = ui.add_enabled(can_add && has_source, egui::Button::new("Add"));
+            let add = if has_source {
+                add
+            } else {
+                add.on_disabled_hover_text("No `item` template to start new items from")
+            };
+            if add.clicked() {
+                action = Some(ObjectAction::Add);
+            }
         }
 
-        if editable && ui.button("Add").clicked() {
-            objects.push(Default::default());
+        if let Some(action) = action {
+            action.apply(items, &mut ids, item.as_ref());
         }
+        ui.data_mut(|d| d.insert_temp(ids_key, ids));
     }
 
-    fn render_array_mixed(ui: &mut Ui, values: &mut Vec<Value>, fonts: FormFonts, indent: f32) {
-        for (i, v) in values.iter().enumerate() {
-            ui.horizontal(|ui| {
-                ui.add_space(indent);
-                ui.label(
-                    egui::RichText::new(format!("[{}]: {}", i, v))
-                        .font(fonts.text.clone())
-                        .color(egui::Color32::LIGHT_GRAY),
-                );
-            });
-        }
+    fn render_array_mixed(
+        ui: &mut Ui,
+        values: &mut Vec<Value>,
+        path: &str,
+        fonts: FormFonts,
+        editable: bool,
+        indent: f32,
+    ) {
+        let editor = ValueEditor {
+            label_font: fonts.label,
+            text_font: fonts.text,
+            editable,
+        };
+        editor.show_items(ui, values, ui.make_persistent_id(("mixed", path)), indent);
     }
 
     fn render_array_enums(
         ui: &mut Ui,
-        enums: &mut Vec<EnumField>,
+        value: &mut ArrayValue,
+        ctx: &FieldContext<'_>,
         fonts: FormFonts,
         editable: bool,
         template: bool,
         indent: f32,
     ) {
+        let ArrayValue::Enums {
+            options_from,
+            free_text,
+            items: enums,
+        } = value
+        else {
+            return;
+        };
         let mut remove_index: Option<usize> = None;
 
+        let mut reorder = Reorder::new(ui.id().with("reorder"), enums.len());
         for (i, enum_field) in enums.iter_mut().enumerate() {
-            ui.horizontal(|ui| {
+            let choices = EnumChoices {
+                options: ctx
+                    .options
+                    .options_for(options_from.as_deref(), &enum_field.options),
+                source: options_from.as_deref(),
+                free_text: free_text.unwrap_or(false),
+            };
+            let row = ui.horizontal(|ui| {
                 ui.add_space(indent);
-                ui.label(egui::RichText::new(format!("[{}]", i)).font(fonts.label.clone()));
-
-                if !enum_field.options.is_empty() && !enum_field.options.contains(&enum_field.value)
-                {
-                    enum_field.value = enum_field.options[0].clone();
+                if editable {
+                    reorder.handle(ui, i);
                 }
+                ui.label(egui::RichText::new(format!("[{}]", i)).font(fonts.label.clone()));
 
-                let combo_id =
-                    ui.make_persistent_id(format!("array_enum_{}_{}", i, enum_field.value));
-
-                egui::ComboBox::from_id_salt(combo_id)
-                    .selected_text(if enum_field.value.is_empty() {
-                        "Select..."
-                    } else {
-                        enum_field.value.as_str()
-                    })
-                    .show_ui(ui, |ui| {
-                        for option in enum_field.options.iter() {
-                            ui.selectable_value(
-                                &mut enum_field.value,
-                                option.clone(),
-                                option.as_str(),
-                            );
-                        }
-                    });
+                let combo_id = ui.make_persistent_id(("array_enum", i));
+                Self::render_enum_selector(ui, combo_id, &mut enum_field.value, &choices);
 
                 if editable && ui.button("X").clicked() {
                     remove_index = Some(i);
                 }
             });
+            reorder.drop_target(ui, &row.response, i);
         }
+        reorder.apply(ui, enums);
 
         if let Some(i) = remove_index {
             enums.remove(i);
@@ -700,6 +1633,151 @@ impl Form {
 // HELPER STRUCTURES
 // ==============================================
 
+/// The table or array field a container renderer draws.
+#[derive(Clone, Copy)]
+struct Node<'a> {
+    key: &'a str,
+    path: &'a str,
+    label: Option<&'a str>,
+    description: Option<&'a str>,
+    level: usize,
+    editable: bool,
+    template: bool,
+}
+
+impl Node<'_> {
+    fn indent(&self) -> f32 {
+        8.0 * self.level as f32
+    }
+}
+
+/// Per-frame data shared by every field renderer.
+struct FieldContext<'a> {
+    form_config: &'a FormConfig,
+    conditions: &'a Conditions,
+    computed: &'a Computations,
+    options: &'a OptionSets,
+    textures: &'a RefCell<TextureCache>,
+    widgets: &'a WidgetRegistry,
+    /// Path of the field focused or clicked this frame, see [`Form::help_field`].
+    help_field: &'a RefCell<Option<String>>,
+    sections: &'a RefCell<Sections>,
+    /// Field to scroll into view, see [`FieldContext::take_jump`].
+    jump_to: &'a RefCell<Option<String>>,
+    search: Option<&'a Search>,
+}
+
+impl FieldContext<'_> {
+    /// Whether to scroll the field at `path` into view. True once per jump.
+    fn take_jump(&self, path: &str) -> bool {
+        let mut jump_to = self.jump_to.borrow_mut();
+        let jump = jump_to.as_deref() == Some(path);
+        if jump {
+            *jump_to = None;
+        }
+        jump
+    }
+}
+
+/// Query of the find bar and the fields it matches.
+#[derive(Default)]
+struct Search {
+    query: String,
+    /// Paths of the matching fields, in document order.
+    matches: Vec<String>,
+    /// Match last moved to with Enter or the arrows.
+    current: Option<String>,
+    /// Whether the query field should take focus on the next frame.
+    focus: bool,
+}
+
+impl Search {
+    fn position(&self) -> Option<usize> {
+        let current = self.current.as_deref()?;
+        self.matches.iter().position(|m| m == current)
+    }
+}
+
+/// The options an enum can take and where they come from.
+struct EnumChoices<'a> {
+    options: Vec<String>,
+    /// Name of the `options_from` set, if any.
+    source: Option<&'a str>,
+    free_text: bool,
+}
+
+enum ObjectAction {
+    Swap(usize, usize),
+    Duplicate(usize),
+    Remove(usize),
+    Add,
+}
+
+impl ObjectAction {
+    /// Applies the action to the items of an object array and to their ids.
+    fn apply(
+        self,
+        items: &mut Vec<IndexMap<String, Field>>,
+        ids: &mut ItemIds,
+        template: Option<&IndexMap<String, Field>>,
+    ) {
+        match self {
+            Self::Swap(a, b) => {
+                items.swap(a, b);
+                ids.ids.swap(a, b);
+            }
+            Self::Duplicate(i) => {
+                if let Some(copy) = items.get(i).cloned() {
+                    items.insert(i + 1, copy);
+                    let id = ids.fresh();
+                    ids.ids.insert(i + 1, id);
+                }
+            }
+            Self::Remove(i) => {
+                items.remove(i);
+                ids.ids.remove(i);
+            }
+            Self::Add => {
+                if let Some(new_item) = template.or(items.last()).cloned() {
+                    items.push(new_item);
+                    let id = ids.fresh();
+                    ids.ids.push(id);
+                }
+            }
+        }
+    }
+}
+
+/// Ids of the items of an object array that move along with them, so each item keeps
+/// its collapsed state when the items are reordered.
+#[derive(Clone, Default)]
+struct ItemIds {
+    ids: Vec<u64>,
+    next: u64,
+}
+
+impl ItemIds {
+    /// Hands out new ids if the number of items changed elsewhere, e.g. by an undo.
+    fn fit(&mut self, count: usize) {
+        if self.ids.len() != count {
+            self.ids.clear();
+            for _ in 0..count {
+                let id = self.fresh();
+                self.ids.push(id);
+            }
+        }
+    }
+
+    fn get(&self, index: usize) -> u64 {
+        self.ids.get(index).copied().unwrap_or_default()
+    }
+
+    fn fresh(&mut self) -> u64 {
+        self.next += 1;
+        self.next
+    }
+}
+
 #[derive(Clone)]
 struct FormFonts {
     header: FontId,
@@ -718,3 +1796,59 @@ impl FormFonts {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::Form;
+    use crate::editor::{
+        form::{diff, form::Document, schema::SchemaRegistry},
+        history::History,
+    };
+
+    const STONE: &str =
+        "[data]\ntype = \"table\"\n\n[data.hardness]\ntype = \"float\"\nvalue = 1.5\n";
+
+    #[test]
+    fn saves_and_reverts_edits_as_changes() {
+        let dir = std::env::temp_dir().join(format!("atom_editor_form_{}", std::process::id()));
+        fs::create_dir_all(&dir).expect("temp folder");
+        let path = dir.join("stone.toml");
+        fs::write(&path, STONE).expect("document written");
+        let base = Document::from_toml(STONE).expect("valid document");
+        let mut form = Form::open(base.clone(), path.clone());
+        assert!(!form.is_dirty(), "just opened");
+
+        form.set_field_value("data.hardness", 3.0.into())
+            .expect("fits the field");
+        assert!(form.is_dirty(), "edited");
+        let changes = diff::diff(&base, form.document());
+        if let Some(change) = changes.first() {
+            form.revert_change(&base, change);
+        }
+        assert!(
+            !form.is_dirty(),
+            "reverting the only edit leaves nothing to save"
+        );
+
+        form.set_field_value("data.hardness", 3.0.into())
+            .expect("fits the field");
+        let mut history = History::default();
+        history.push(form.save().expect("saved"));
+        assert!(!form.is_dirty(), "saved");
+        assert_eq!(
+            history.undo_label(),
+            Some("Save stone.toml"),
+            "undoable save"
+        );
+
+        history.undo();
+        let restored = SchemaRegistry::default().load_document(&path);
+        fs::remove_dir_all(&dir).ok();
+        assert!(
+            restored.is_ok_and(|document| diff::diff(&base, &document).is_empty()),
+            "undo writes the file as it was"
+        );
+    }
+}
@@ -0,0 +1,99 @@
+use egui::{RichText, Ui};
+
+use crate::editor::form::{
+    form::Field,
+    markdown::{self, Block, Inline},
+};
+
+/// Shows the help of `field`: its description, Markdown help, documentation link and
+/// examples. Returns the example the user chose to use, if any.
+pub fn show(ui: &mut Ui, field: &Field, path: &str) -> Option<toml::Value> {
+    ui.heading(field.label.as_deref().unwrap_or(path));
+    ui.label(RichText::new(path).monospace().weak());
+    if let Some(description) = &field.description {
+        ui.add_space(4.0);
+        ui.label(description);
+    }
+    ui.separator();
+
+    match &field.help {
+        Some(help) => show_markdown(ui, help),
+        None => {
+            ui.label(RichText::new("The schema has no help for this field.").weak());
+        }
+    }
+    if let Some(docs) = &field.docs {
+        ui.add_space(8.0);
+        ui.hyperlink_to("📖 Documentation", docs);
+    }
+
+    let examples = field.examples.as_deref().unwrap_or_default();
+    if examples.is_empty() {
+        return None;
+    }
+    let mut chosen = None;
+    ui.add_space(8.0);
+    ui.label(RichText::new("Examples").strong());
+    for example in examples {
+        ui.horizontal(|ui| {
+            ui.label(RichText::new(example.to_string()).monospace());
+            if ui.small_button("Use").clicked() {
+                chosen = Some(example.clone());
+            }
+        });
+    }
+    chosen
+}
+
+fn show_markdown(ui: &mut Ui, text: &str) {
+    for block in markdown::parse(text) {
+        match block {
+            Block::Heading(level, inlines) => {
+                ui.add_space(6.0);
+                let size = match level {
+                    1 => 20.0,
+                    2 => 17.0,
+                    _ => 15.0,
+                };
+                show_inline(ui, &inlines, |text| RichText::new(text).size(size).strong());
+            }
+            Block::Paragraph(inlines) => {
+                ui.add_space(4.0);
+                show_inline(ui, &inlines, |text| RichText::new(text));
+            }
+            Block::Bullet(inlines) => {
+                ui.horizontal(|ui| {
+                    ui.label("•");
+                    show_inline(ui, &inlines, |text| RichText::new(text));
+                });
+            }
+            Block::Code(code) => {
+                ui.add_space(4.0);
+                egui::Frame::new()
+                    .fill(ui.visuals().extreme_bg_color)
+                    .inner_margin(6.0)
+                    .corner_radius(3.0)
+                    .show(ui, |ui| {
+                        ui.set_width(ui.available_width());
+                        ui.label(RichText::new(code).monospace());
+                    });
+            }
+        }
+    }
+}
+
+/// Lays out the runs of a block as wrapped text, styling plain runs with `style`.
+fn show_inline(ui: &mut Ui, inlines: &[Inline], style: impl Fn(&str) -> RichText) {
+    ui.horizontal_wrapped(|ui| {
+        ui.spacing_mut().item_spacing.x = 0.0;
+        for inline in inlines {
+            match inline {
+                Inline::Text(text) => ui.label(style(text)),
+                Inline::Strong(text) => ui.label(style(text).strong()),
+                Inline::Emphasis(text) => ui.label(style(text).italics()),
+                Inline::Code(text) => ui.label(style(text).code()),
+                Inline::Link { text, url } => ui.hyperlink_to(text, url),
+            };
+        }
+    });
+}
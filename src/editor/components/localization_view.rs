@@ -0,0 +1,312 @@
+use std::{fs, path::PathBuf};
+
+use egui::{Color32, FontId, Ui};
+
+use crate::editor::{
+    components::form_config::FormConfig,
+    form::{
+        localization::{Cell, Localization},
+        schema::SchemaRegistry,
+        translations::{self, ImportReport, TranslationFormat},
+    },
+    history::History,
+};
+
+/// Grid of every translation key of the project against every locale.
+pub struct LocalizationView {
+    paths: Vec<PathBuf>,
+    localization: Localization,
+    new_locale: String,
+    filter: String,
+    /// Hide rows translated in every locale.
+    only_incomplete: bool,
+    export_format: TranslationFormat,
+    export_locale: usize,
+    /// Result of the last import or export.
+    report: Option<Result<ImportReport, String>>,
+}
+
+impl LocalizationView {
+    pub fn open(paths: Vec<PathBuf>, schemas: &SchemaRegistry) -> Self {
+        let localization = Localization::load(&paths, schemas);
+        Self {
+            paths,
+            localization,
+            new_locale: String::new(),
+            filter: String::new(),
+            only_incomplete: false,
+            export_format: TranslationFormat::Lang,
+            export_locale: 0,
+            report: None,
+        }
+    }
+
+    /// Reads the documents again, dropping unsaved edits.
+    pub fn reload(&mut self, schemas: &SchemaRegistry) {
+        self.localization = Localization::load(&self.paths, schemas);
+    }
+
+    /// Shows the view. Returns `false` once the user closes it.
+    pub fn show(&mut self, ui: &mut Ui, form_config: &FormConfig, history: &mut History) -> bool {
+        let text_font = FontId::monospace(form_config.font_size_text);
+        let mut open = true;
+
+        ui.horizontal(|ui| {
+            ui.label(
+                egui::RichText::new(format!(
+                    "Localization - {} files",
+                    self.localization.document_count()
+                ))
+                .font(FontId::proportional(form_config.font_size_header))
+                .strong(),
+            );
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.button("Close").clicked() {
+                    open = false;
+                }
+                if ui
+                    .add_enabled(self.localization.is_dirty(), egui::Button::new("Save"))
+                    .clicked()
+                {
+                    if let Some(change) = self.localization.save() {
+                        history.push(change);
+                    }
+                }
+            });
+        });
+
+        for error in &self.localization.errors {
+            ui.colored_label(Color32::RED, error);
+        }
+
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.new_locale)
+                    .hint_text("e.g. pt_BR")
+                    .desired_width(120.0),
+            );
+            let locale = self.new_locale.trim();
+            let valid = !locale.is_empty()
+                && !locale.contains(['.', ' '])
+                && !self.localization.locales().iter().any(|l| l == locale);
+            if ui
+                .add_enabled(valid, egui::Button::new("Add locale"))
+                .on_hover_text("Adds the locale to every document of the project")
+                .clicked()
+            {
+                self.localization.add_locale(locale);
+                self.new_locale.clear();
+            }
+            ui.separator();
+            ui.add(
+                egui::TextEdit::singleline(&mut self.filter)
+                    .hint_text("Filter keys")
+                    .desired_width(160.0),
+            );
+            ui.checkbox(&mut self.only_incomplete, "Only incomplete");
+        });
+        self.show_exchange(ui);
+        ui.separator();
+
+        if self.localization.entries().is_empty() {
+            ui.label("No document of the project has translations.");
+            return open;
+        }
+
+        egui::ScrollArea::horizontal()
+            .id_salt("localization_scroll")
+            .show(ui, |ui| self.show_grid(ui, &text_font));
+
+        open
+    }
+
+    /// Import and export of translation files.
+    fn show_exchange(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            if ui.button("Import…").clicked() {
+                self.import();
+            }
+            ui.separator();
+            egui::ComboBox::from_id_salt("translation_format")
+                .selected_text(self.export_format.extension())
+                .show_ui(ui, |ui| {
+                    for format in TranslationFormat::ALL {
+                        ui.selectable_value(&mut self.export_format, format, format.extension());
+                    }
+                });
+            if self.export_format.is_per_locale() {
+                let locales = self.localization.locales();
+                egui::ComboBox::from_id_salt("translation_locale")
+                    .selected_text(
+                        locales
+                            .get(self.export_locale)
+                            .map(String::as_str)
+                            .unwrap_or_default(),
+                    )
+                    .show_ui(ui, |ui| {
+                        for (index, locale) in locales.iter().enumerate() {
+                            ui.selectable_value(&mut self.export_locale, index, locale);
+                        }
+                    });
+            }
+            if ui.button("Export…").clicked() {
+                self.export();
+            }
+        });
+
+        match &self.report {
+            Some(Ok(report)) => {
+                ui.label(format!("Imported {} strings.", report.imported));
+                if !report.new_locales.is_empty() {
+                    ui.label(format!("New locales: {}", report.new_locales.join(", ")));
+                }
+                if !report.unknown_keys.is_empty() {
+                    ui.collapsing(
+                        format!("{} keys no longer exist", report.unknown_keys.len()),
+                        |ui| {
+                            for key in &report.unknown_keys {
+                                ui.label(key);
+                            }
+                        },
+                    );
+                }
+                if !report.changed_sources.is_empty() {
+                    ui.collapsing(
+                        format!(
+                            "⚠ {} source strings changed since the export",
+                            report.changed_sources.len()
+                        ),
+                        |ui| {
+                            for key in &report.changed_sources {
+                                ui.label(key);
+                            }
+                        },
+                    );
+                }
+            }
+            Some(Err(err)) => {
+                ui.colored_label(Color32::RED, err);
+            }
+            None => {}
+        }
+    }
+
+    fn export(&mut self) {
+        let format = self.export_format;
+        let name = if format.is_per_locale() {
+            self.localization
+                .locales()
+                .get(self.export_locale)
+                .cloned()
+                .unwrap_or_default()
+        } else {
+            "translations".to_owned()
+        };
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter(format.extension(), &[format.extension()])
+            .set_file_name(format!("{name}.{}", format.extension()))
+            .save_file()
+        else {
+            return;
+        };
+        let content = translations::export(&self.localization, format, self.export_locale);
+        self.report = fs::write(&path, content)
+            .err()
+            .map(|err| Err(format!("{}: {}", path.display(), err)));
+    }
+
+    /// Reads a translation file into the grid. Nothing is written until "Save".
+    fn import(&mut self) {
+        let extensions = TranslationFormat::ALL.map(TranslationFormat::extension);
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("Translations", &extensions)
+            .pick_file()
+        else {
+            return;
+        };
+        let Some(format) = TranslationFormat::from_path(&path) else {
+            return;
+        };
+        let locale = path.file_stem().and_then(|stem| stem.to_str());
+        let result = fs::read_to_string(&path)
+            .map_err(|err| err.to_string())
+            .and_then(|text| {
+                translations::import(&mut self.localization, format, &text, locale)
+                    .map_err(|err| err.to_string())
+            });
+        self.report = Some(result.map_err(|err| format!("{}: {}", path.display(), err)));
+    }
+
+    fn show_grid(&mut self, ui: &mut Ui, text_font: &FontId) {
+        let locale_count = self.localization.locales().len();
+        let filter = self.filter.to_lowercase();
+        let mut edit = None;
+
+        egui::Grid::new("localization_grid")
+            .num_columns(locale_count + 1)
+            .striped(true)
+            .spacing([12.0, 6.0])
+            .show(ui, |ui| {
+                ui.label(egui::RichText::new("Key").strong());
+                for (locale, name) in self.localization.locales().iter().enumerate() {
+                    let (done, total) = self.localization.completion(locale);
+                    let percent = if total == 0 { 100 } else { done * 100 / total };
+                    ui.vertical(|ui| {
+                        ui.label(egui::RichText::new(name).strong());
+                        ui.label(format!("{percent}% ({done}/{total})"));
+                    });
+                }
+                ui.end_row();
+
+                for (index, entry) in self.localization.entries().iter().enumerate() {
+                    if !filter.is_empty() && !entry.key.to_lowercase().contains(&filter) {
+                        continue;
+                    }
+                    let complete = (0..locale_count)
+                        .all(|l| matches!(self.localization.cell(index, l), Cell::Text(_)));
+                    if self.only_incomplete && complete {
+                        continue;
+                    }
+
+                    let key = ui.label(egui::RichText::new(&entry.key).font(text_font.clone()));
+                    if let Some(path) = self.localization.path_of(entry) {
+                        key.on_hover_text(path.display().to_string());
+                    }
+
+                    for locale in 0..locale_count {
+                        let cell = self.localization.cell(index, locale);
+                        let mut text = match cell {
+                            Cell::Text(text) => text.to_owned(),
+                            Cell::Missing | Cell::Empty => String::new(),
+                        };
+                        let (hint, background) = match cell {
+                            Cell::Missing => ("missing", Some(Color32::from_rgb(110, 40, 40))),
+                            Cell::Empty => ("empty", Some(Color32::from_rgb(110, 90, 30))),
+                            Cell::Text(_) => ("", None),
+                        };
+                        ui.horizontal(|ui| {
+                            let mut text_edit = egui::TextEdit::singleline(&mut text)
+                                .font(text_font.clone())
+                                .hint_text(hint)
+                                .desired_width(200.0);
+                            if let Some(color) = background {
+                                text_edit = text_edit.background_color(color);
+                            }
+                            if ui.add(text_edit).changed() {
+                                edit = Some((index, locale, text.clone()));
+                            }
+                            if let Some(other) = self.localization.duplicate_of(index, locale) {
+                                ui.colored_label(Color32::YELLOW, "⚠")
+                                    .on_hover_text(format!("Nearly the same as {}", other.key));
+                            }
+                        });
+                    }
+                    ui.end_row();
+                }
+            });
+
+        if let Some((entry, locale, text)) = edit {
+            self.localization.set(entry, locale, &text);
+        }
+    }
+}
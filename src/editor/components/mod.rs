@@ -1,3 +1,16 @@
+pub mod batch_form;
+pub mod color_edit;
+pub mod diff_view;
 pub mod form_config;
 pub mod form_view;
+pub mod help_view;
+pub mod localization_view;
+pub mod raw_view;
+pub mod reorder;
+pub mod rich_text;
+pub mod sections;
 pub mod tabs;
+pub mod texture_cache;
+pub mod value_editor;
+pub mod vector_edit;
+pub mod widgets;
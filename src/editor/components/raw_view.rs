@@ -0,0 +1,133 @@
+use egui::{Color32, FontId, TextFormat, Ui, text::LayoutJob};
+
+use crate::editor::form::form::Document;
+
+/// A TOML error and, when known, the 1-based line and column it points at.
+struct RawError {
+    position: Option<(usize, usize)>,
+    message: String,
+}
+
+impl RawError {
+    fn new(text: &str, err: &toml::de::Error) -> Self {
+        let position = err.span().map(|span| {
+            let before = text.get(..span.start).unwrap_or(text);
+            let line = before.matches('\n').count() + 1;
+            let column = before
+                .rsplit('\n')
+                .next()
+                .map_or(0, |line| line.chars().count())
+                + 1;
+            (line, column)
+        });
+        Self {
+            position,
+            message: err.message().trim().to_owned(),
+        }
+    }
+}
+
+/// The TOML text of a document, edited next to its form.
+///
+/// Valid edits replace the form's document; while the text has errors the form keeps
+/// its last valid state.
+pub struct RawView {
+    text: String,
+    /// Serialization of the document the text was last synchronized with.
+    synced: String,
+    error: Option<RawError>,
+}
+
+impl RawView {
+    pub fn new(document: &Document) -> Self {
+        let text = document.to_toml().unwrap_or_default();
+        Self {
+            synced: text.clone(),
+            text,
+            error: None,
+        }
+    }
+
+    pub fn has_error(&self) -> bool {
+        self.error.is_some()
+    }
+
+    /// Rewrites the text after edits in the form. Text with errors is left alone.
+    pub fn sync_from(&mut self, document: &Document) {
+        if self.error.is_some() {
+            return;
+        }
+        let Ok(text) = document.to_toml() else {
+            return;
+        };
+        if text != self.synced {
+            self.text.clone_from(&text);
+            self.synced = text;
+        }
+    }
+
+    /// Shows the text editor. Returns the document parsed from the text once it is edited
+    /// into a valid one.
+    pub fn show(&mut self, ui: &mut Ui, font: &FontId) -> Option<Document> {
+        if let Some(error) = &self.error {
+            let text = match error.position {
+                Some((line, column)) => format!("Line {line}, column {column}: {}", error.message),
+                None => error.message.clone(),
+            };
+            ui.colored_label(ui.visuals().error_fg_color, text);
+        }
+
+        let error_line = self.error.as_ref().and_then(|e| e.position).map(|(l, _)| l);
+        let mut layouter = |ui: &Ui, text: &dyn egui::TextBuffer, wrap_width: f32| {
+            let mut job = highlight(ui, text.as_str(), font, error_line);
+            job.wrap.max_width = wrap_width;
+            ui.fonts_mut(|fonts| fonts.layout_job(job))
+        };
+        let response = ui.add(
+            egui::TextEdit::multiline(&mut self.text)
+                .code_editor()
+                .desired_width(f32::INFINITY)
+                .desired_rows(30)
+                .layouter(&mut layouter),
+        );
+        if !response.changed() {
+            return None;
+        }
+
+        match Document::from_toml(&self.text) {
+            Ok(document) => {
+                self.error = None;
+                self.synced = document.to_toml().unwrap_or_default();
+                Some(document)
+            }
+            Err(err) => {
+                self.error = Some(RawError::new(&self.text, &err));
+                None
+            }
+        }
+    }
+}
+
+/// Lays out `text` with the line `error_line` on a red background.
+fn highlight(ui: &Ui, text: &str, font: &FontId, error_line: Option<usize>) -> LayoutJob {
+    let color = ui.visuals().text_color();
+    let mut job = LayoutJob::default();
+    for (index, line) in text.split_inclusive('\n').enumerate() {
+        let background = if error_line == Some(index + 1) {
+            ui.visuals().error_fg_color.gamma_multiply(0.3)
+        } else {
+            Color32::TRANSPARENT
+        };
+        job.append(
+            line,
+            0.0,
+            TextFormat {
+                font_id: font.clone(),
+                color,
+                background,
+                ..Default::default()
+            },
+        );
+    }
+    job
+}
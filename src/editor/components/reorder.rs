@@ -0,0 +1,214 @@
+use egui::{Id, Response, Sense, Ui};
+use indexmap::IndexMap;
+
+/// What a drag handle carries: the list it belongs to and the element index.
+struct DragItem {
+    list: Id,
+    index: usize,
+}
+
+/// Drag-and-drop and keyboard reordering for one list of rows.
+///
+/// Call [`Reorder::handle`] inside each row, [`Reorder::drop_target`] with the row's
+/// response, and [`Reorder::apply`] once the list has been drawn. A focused handle moves
+/// its row with Alt+Up / Alt+Down.
+pub struct Reorder {
+    list: Id,
+    count: usize,
+    moved: Option<(usize, usize)>,
+    keyboard: bool,
+}
+
+impl Reorder {
+    pub fn new(list: Id, count: usize) -> Self {
+        Self {
+            list,
+            count,
+            moved: None,
+            keyboard: false,
+        }
+    }
+
+    fn handle_id(&self, index: usize) -> Id {
+        self.list.with(("reorder_handle", index))
+    }
+
+    /// Draws the drag handle of row `index`.
+    pub fn handle(&mut self, ui: &mut Ui, index: usize) {
+        let id = self.handle_id(index);
+        let size = egui::vec2(ui.spacing().interact_size.y, ui.spacing().interact_size.y);
+        let (rect, _) = ui.allocate_exact_size(size, Sense::hover());
+        let response = ui
+            .interact(rect, id, Sense::click_and_drag())
+            .on_hover_text("Drag to reorder, or Alt+Up / Alt+Down");
+
+        let color = if response.hovered() || response.has_focus() || response.dragged() {
+            ui.visuals().strong_text_color()
+        } else {
+            ui.visuals().weak_text_color()
+        };
+        ui.painter().text(
+            rect.center(),
+            egui::Align2::CENTER_CENTER,
+            "☰",
+            egui::FontId::proportional(rect.height() * 0.8),
+            color,
+        );
+        if response.hovered() || response.dragged() {
+            ui.ctx().set_cursor_icon(egui::CursorIcon::Grab);
+        }
+
+        response.dnd_set_drag_payload(DragItem {
+            list: self.list,
+            index,
+        });
+
+        if response.has_focus() {
+            let up = egui::KeyboardShortcut::new(egui::Modifiers::ALT, egui::Key::ArrowUp);
+            let down = egui::KeyboardShortcut::new(egui::Modifiers::ALT, egui::Key::ArrowDown);
+            if index > 0 && ui.input_mut(|i| i.consume_shortcut(&up)) {
+                self.moved = Some((index, index - 1));
+                self.keyboard = true;
+            } else if index + 1 < self.count && ui.input_mut(|i| i.consume_shortcut(&down)) {
+                self.moved = Some((index, index + 1));
+                self.keyboard = true;
+            }
+        }
+    }
+
+    /// Accepts handles of this list dropped on `row`, drawing where they would land.
+    pub fn drop_target(&mut self, ui: &Ui, row: &Response, index: usize) {
+        let Some(item) = row.dnd_hover_payload::<DragItem>() else {
+            return;
+        };
+        if item.list != self.list || item.index == index {
+            return;
+        }
+
+        let before = ui
+            .ctx()
+            .pointer_interact_pos()
+            .is_some_and(|pos| pos.y < row.rect.center().y);
+        let y = if before {
+            row.rect.top()
+        } else {
+            row.rect.bottom()
+        };
+        ui.painter()
+            .hline(row.rect.x_range(), y, ui.visuals().selection.stroke);
+
+        if ui.input(|i| i.pointer.any_released()) {
+            egui::DragAndDrop::clear_payload(ui.ctx());
+            let from = item.index;
+            let to = landing_index(from, index, before);
+            if from != to {
+                self.moved = Some((from, to));
+            }
+        }
+    }
+
+    /// Applies the requested move to `items`.
+    pub fn apply<T>(&self, ui: &Ui, items: &mut Vec<T>) {
+        if let Some((from, to)) = self.moved {
+            if from < items.len() && to < items.len() {
+                let item = items.remove(from);
+                items.insert(to, item);
+                self.follow_focus(ui, to);
+            }
+        }
+    }
+
+    /// Applies the requested move to the entries of a table.
+    pub fn apply_map<V>(&self, ui: &Ui, map: &mut IndexMap<String, V>) {
+        if let Some((from, to)) = self.moved {
+            if from < map.len() && to < map.len() {
+                map.move_index(from, to);
+                self.follow_focus(ui, to);
+            }
+        }
+    }
+
+    /// Keeps the keyboard focus on the handle of the row that was moved.
+    fn follow_focus(&self, ui: &Ui, to: usize) {
+        if self.keyboard {
+            ui.memory_mut(|m| m.request_focus(self.handle_id(to)));
+        }
+    }
+}
+
+/// The index a row dragged from `from` ends up at once it is removed from its old place,
+/// when dropped `before` or after row `target`.
+fn landing_index(from: usize, target: usize, before: bool) -> usize {
+    match (before, from < target) {
+        (true, true) => target - 1,
+        (true, false) | (false, true) => target,
+        (false, false) => target + 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use egui::{Id, Ui};
+    use indexmap::IndexMap;
+
+    use super::{Reorder, landing_index};
+
+    /// Runs `f` with a `Ui` of a headless context.
+    fn with_ui(f: impl FnOnce(&Ui)) {
+        let ctx = egui::Context::default();
+        let mut f = Some(f);
+        let _output = ctx.run(egui::RawInput::default(), |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                if let Some(f) = f.take() {
+                    f(ui);
+                }
+            });
+        });
+    }
+
+    fn moved(from: usize, to: usize) -> Reorder {
+        let mut reorder = Reorder::new(Id::new("list"), 3);
+        reorder.moved = Some((from, to));
+        reorder
+    }
+
+    #[test]
+    fn lands_next_to_the_target_row() {
+        assert_eq!(landing_index(0, 2, true), 1, "before a later row");
+        assert_eq!(landing_index(0, 2, false), 2, "after a later row");
+        assert_eq!(landing_index(2, 0, true), 0, "before an earlier row");
+        assert_eq!(landing_index(2, 0, false), 1, "after an earlier row");
+    }
+
+    #[test]
+    fn moves_table_keys() {
+        let mut map: IndexMap<String, i32> = [("a", 1), ("b", 2), ("c", 3)]
+            .into_iter()
+            .map(|(key, value)| (key.to_owned(), value))
+            .collect();
+        with_ui(|ui| moved(0, 2).apply_map(ui, &mut map));
+        assert_eq!(
+            map.keys().map(String::as_str).collect::<Vec<_>>(),
+            ["b", "c", "a"],
+            "first key moved last"
+        );
+        assert_eq!(map.get("a"), Some(&1), "values move with their keys");
+
+        with_ui(|ui| moved(2, 1).apply_map(ui, &mut map));
+        assert_eq!(
+            map.keys().map(String::as_str).collect::<Vec<_>>(),
+            ["b", "a", "c"],
+            "last key moved up"
+        );
+    }
+
+    #[test]
+    fn ignores_moves_out_of_range() {
+        let mut items = vec!["a", "b"];
+        with_ui(|ui| moved(0, 2).apply(ui, &mut items));
+        assert_eq!(items, ["a", "b"], "the list shrank since the move");
+
+        with_ui(|ui| moved(1, 0).apply(ui, &mut items));
+        assert_eq!(items, ["b", "a"], "moved up");
+    }
+}
@@ -0,0 +1,98 @@
+use egui::{Color32, FontId, Stroke, TextFormat, Ui, text::LayoutJob};
+
+use crate::editor::form::{
+    color::Rgba,
+    formatting::{self, Code, Segment},
+};
+
+/// Background of the preview, close to the game's tooltips so light colors stay legible.
+const PREVIEW_BACKGROUND: Color32 = Color32::from_rgb(24, 8, 32);
+/// Color of text without a color code in the preview.
+const PREVIEW_TEXT: Color32 = Color32::from_rgb(255, 255, 255);
+
+fn color32(color: Rgba) -> Color32 {
+    Color32::from_rgb(color.r, color.g, color.b)
+}
+
+/// Lays out text being edited with its formatting codes highlighted: color codes in
+/// their color, other codes in the accent color and unknown codes in the error color.
+pub fn highlight(ui: &Ui, text: &str, font: &FontId) -> LayoutJob {
+    let visuals = ui.visuals();
+    let code_background = visuals.faint_bg_color;
+    let mut job = LayoutJob::default();
+    for segment in formatting::segments(text) {
+        let format = match segment {
+            Segment::Text { .. } => TextFormat {
+                font_id: font.clone(),
+                color: visuals.text_color(),
+                ..Default::default()
+            },
+            Segment::Code { code, .. } => TextFormat {
+                font_id: font.clone(),
+                color: match code {
+                    Some(Code::Color(color)) => color32(color),
+                    Some(_) => visuals.hyperlink_color,
+                    None => visuals.error_fg_color,
+                },
+                background: code_background,
+                underline: if code.is_none() {
+                    Stroke::new(1.0, visuals.error_fg_color)
+                } else {
+                    Stroke::NONE
+                },
+                ..Default::default()
+            },
+        };
+        let text = match segment {
+            Segment::Text { text, .. } | Segment::Code { text, .. } => text,
+        };
+        job.append(text, 0.0, format);
+    }
+    job
+}
+
+/// Shows `text` the way the game renders it, without the formatting codes.
+///
+/// Bold has no counterpart in the editor's fonts and is shown as regular text.
+pub fn preview(ui: &mut Ui, text: &str, font: &FontId) {
+    let mut job = LayoutJob::default();
+    for segment in formatting::segments(text) {
+        let Segment::Text { text, style } = segment else {
+            continue;
+        };
+        let color = style.color.map_or(PREVIEW_TEXT, color32);
+        let line = |enabled: bool| {
+            if enabled {
+                Stroke::new(1.0, color)
+            } else {
+                Stroke::NONE
+            }
+        };
+        let format = TextFormat {
+            font_id: font.clone(),
+            color,
+            italics: style.italic,
+            underline: line(style.underline),
+            strikethrough: line(style.strikethrough),
+            ..Default::default()
+        };
+        // Obfuscated text cycles through random glyphs in game.
+        let shown = if style.obfuscated {
+            text.chars()
+                .map(|c| if c == '\n' { c } else { '▒' })
+                .collect()
+        } else {
+            text.to_owned()
+        };
+        job.append(&shown, 0.0, format);
+    }
+
+    egui::Frame::new()
+        .fill(PREVIEW_BACKGROUND)
+        .inner_margin(6.0)
+        .corner_radius(3.0)
+        .show(ui, |ui| {
+            ui.set_width(ui.available_width());
+            ui.label(job);
+        });
+}
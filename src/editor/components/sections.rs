@@ -0,0 +1,36 @@
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::editor::form::form::Document;
+
+/// Which tables of a document are expanded, by field path. Kept per document across
+/// sessions; tables start collapsed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Sections {
+    expanded: BTreeSet<String>,
+}
+
+impl Sections {
+    pub fn is_expanded(&self, path: &str) -> bool {
+        self.expanded.contains(path)
+    }
+
+    pub fn set_expanded(&mut self, path: &str, expanded: bool) {
+        if expanded {
+            self.expanded.insert(path.to_owned());
+        } else {
+            self.expanded.remove(path);
+        }
+    }
+
+    /// Expands every table of `document`, nested ones included.
+    pub fn expand_all(&mut self, document: &Document) {
+        self.expanded.extend(document.table_paths());
+    }
+
+    pub fn collapse_all(&mut self) {
+        self.expanded.clear();
+    }
+}
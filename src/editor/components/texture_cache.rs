@@ -0,0 +1,52 @@
+use std::collections::HashMap;
+
+use egui::{TextureHandle, TextureOptions};
+use image::imageops::FilterType;
+
+/// Textures of image fields, loaded from the file each field points at.
+///
+/// Paths that are not image files (e.g. `namespace:item/texture` ids) are remembered as
+/// missing so they are not read again every frame.
+#[derive(Default)]
+pub struct TextureCache {
+    textures: HashMap<String, Option<TextureHandle>>,
+}
+
+impl TextureCache {
+    /// The texture of the image at `path`, loading it on first use.
+    pub fn get(&mut self, ctx: &egui::Context, path: &str) -> Option<&TextureHandle> {
+        if path.is_empty() {
+            return None;
+        }
+        self.textures
+            .entry(path.to_owned())
+            .or_insert_with(|| Self::load(ctx, path))
+            .as_ref()
+    }
+
+    /// Drops the texture of `path`, so it is read again, e.g. after the file changed.
+    pub fn forget(&mut self, path: &str) {
+        self.textures.remove(path);
+    }
+
+    fn load(ctx: &egui::Context, path: &str) -> Option<TextureHandle> {
+        let mut img = image::open(path).ok()?.to_rgba8();
+        let (width, height) = (img.width(), img.height());
+        let min_size = 128;
+
+        // Block textures are tiny; scale them up with nearest-neighbour so they stay sharp.
+        if width < min_size || height < min_size {
+            let scale_w = min_size.max(width);
+            let scale_h = min_size.max(height);
+            img = image::imageops::resize(&img, scale_w, scale_h, FilterType::Nearest);
+        }
+
+        let size = [img.width() as _, img.height() as _];
+        let pixels = img.into_raw();
+        Some(ctx.load_texture(
+            path,
+            egui::ColorImage::from_rgba_unmultiplied(size, &pixels),
+            TextureOptions::NEAREST,
+        ))
+    }
+}
@@ -0,0 +1,248 @@
+use egui::{FontId, Id, Ui};
+use toml::{Table, Value};
+
+use crate::editor::{
+    components::reorder::Reorder,
+    form::{convert::convert_value, form::FieldType},
+};
+
+/// Editor for untyped TOML values, used by mixed and nested arrays.
+///
+/// Every element picks its own type; arrays and tables are edited recursively.
+pub struct ValueEditor {
+    pub label_font: FontId,
+    pub text_font: FontId,
+    /// Whether elements can be added, removed, reordered and retyped.
+    pub editable: bool,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ValueKind {
+    String,
+    Integer,
+    Float,
+    Boolean,
+    Array,
+    Table,
+}
+
+impl ValueKind {
+    const ALL: [Self; 6] = [
+        Self::String,
+        Self::Integer,
+        Self::Float,
+        Self::Boolean,
+        Self::Array,
+        Self::Table,
+    ];
+
+    fn of(value: &Value) -> Option<Self> {
+        match value {
+            Value::String(_) => Some(Self::String),
+            Value::Integer(_) => Some(Self::Integer),
+            Value::Float(_) => Some(Self::Float),
+            Value::Boolean(_) => Some(Self::Boolean),
+            Value::Array(_) => Some(Self::Array),
+            Value::Table(_) => Some(Self::Table),
+            Value::Datetime(_) => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::String => "String",
+            Self::Integer => "Integer",
+            Self::Float => "Float",
+            Self::Boolean => "Boolean",
+            Self::Array => "Array",
+            Self::Table => "Table",
+        }
+    }
+
+    /// Converts `value` to this kind, keeping as much of it as possible.
+    fn convert(self, value: &Value) -> Value {
+        let scalar = |field_type| {
+            let source = match value {
+                Value::Array(items) => items.first()?,
+                other => other,
+            };
+            convert_value(source, field_type)
+        };
+        match self {
+            Self::String => scalar(FieldType::String).unwrap_or_else(|| String::new().into()),
+            Self::Integer => scalar(FieldType::Integer).unwrap_or(Value::Integer(0)),
+            Self::Float => scalar(FieldType::Float).unwrap_or(Value::Float(0.0)),
+            Self::Boolean => scalar(FieldType::Boolean).unwrap_or(Value::Boolean(false)),
+            Self::Array => Value::Array(vec![value.clone()]),
+            Self::Table => Value::Table(Table::new()),
+        }
+    }
+}
+
+enum ItemAction {
+    Remove(usize),
+    Swap(usize, usize),
+}
+
+impl ValueEditor {
+    /// Shows the elements of an array, one row each, followed by an "Add" button.
+    pub fn show_items(&self, ui: &mut Ui, items: &mut Vec<Value>, id: Id, indent: f32) {
+        let mut action = None;
+        let count = items.len();
+
+        let mut reorder = Reorder::new(id.with("reorder"), count);
+        for (i, item) in items.iter_mut().enumerate() {
+            let item_id = id.with(i);
+            let row = ui.horizontal(|ui| {
+                ui.add_space(indent);
+                if self.editable {
+                    reorder.handle(ui, i);
+                }
+                ui.label(egui::RichText::new(format!("[{i}]")).font(self.label_font.clone()));
+                self.show_kind_selector(ui, item, item_id);
+                self.show_scalar(ui, item);
+
+                if self.editable {
+                    if ui.add_enabled(i > 0, egui::Button::new("⬆")).clicked() {
+                        action = Some(ItemAction::Swap(i, i - 1));
+                    }
+                    if ui
+                        .add_enabled(i + 1 < count, egui::Button::new("⬇"))
+                        .clicked()
+                    {
+                        action = Some(ItemAction::Swap(i, i + 1));
+                    }
+                    if ui.button("X").clicked() {
+                        action = Some(ItemAction::Remove(i));
+                    }
+                }
+            });
+            reorder.drop_target(ui, &row.response, i);
+            self.show_children(ui, item, item_id, indent);
+        }
+        reorder.apply(ui, items);
+
+        match action {
+            Some(ItemAction::Remove(i)) => {
+                items.remove(i);
+            }
+            Some(ItemAction::Swap(a, b)) => items.swap(a, b),
+            None => {}
+        }
+
+        if self.editable {
+            ui.horizontal(|ui| {
+                ui.add_space(indent);
+                if ui.button("Add").clicked() {
+                    // New elements take the type of the last one.
+                    let kind = items
+                        .last()
+                        .and_then(ValueKind::of)
+                        .unwrap_or(ValueKind::String);
+                    items.push(kind.convert(&Value::String(String::new())));
+                }
+            });
+        }
+    }
+
+    /// Shows the entries of an inline table, followed by a key field to add entries.
+    fn show_table(&self, ui: &mut Ui, table: &mut Table, id: Id, indent: f32) {
+        let mut remove_key = None;
+
+        for (key, value) in table.iter_mut() {
+            let entry_id = id.with(key);
+            ui.horizontal(|ui| {
+                ui.add_space(indent);
+                ui.label(egui::RichText::new(key).font(self.label_font.clone()));
+                self.show_kind_selector(ui, value, entry_id);
+                self.show_scalar(ui, value);
+                if self.editable && ui.button("X").clicked() {
+                    remove_key = Some(key.clone());
+                }
+            });
+            self.show_children(ui, value, entry_id, indent);
+        }
+
+        if let Some(key) = remove_key {
+            table.remove(&key);
+        }
+
+        if self.editable {
+            ui.horizontal(|ui| {
+                ui.add_space(indent);
+                let text_id = id.with("new_key");
+                let mut new_key =
+                    ui.data_mut(|d| d.get_temp::<String>(text_id).unwrap_or_default());
+                ui.add(egui::TextEdit::singleline(&mut new_key).desired_width(120.0));
+                if ui.button("Add key").clicked()
+                    && !new_key.is_empty()
+                    && !table.contains_key(&new_key)
+                {
+                    table.insert(std::mem::take(&mut new_key), String::new().into());
+                }
+                ui.data_mut(|d| d.insert_temp(text_id, new_key));
+            });
+        }
+    }
+
+    fn show_kind_selector(&self, ui: &mut Ui, value: &mut Value, id: Id) {
+        let Some(current) = ValueKind::of(value) else {
+            return;
+        };
+        if !self.editable {
+            return;
+        }
+
+        let mut selected = current;
+        egui::ComboBox::from_id_salt(id.with("kind"))
+            .width(80.0)
+            .selected_text(current.name())
+            .show_ui(ui, |ui| {
+                for kind in ValueKind::ALL {
+                    ui.selectable_value(&mut selected, kind, kind.name());
+                }
+            });
+        if selected != current {
+            *value = selected.convert(value);
+        }
+    }
+
+    fn show_scalar(&self, ui: &mut Ui, value: &mut Value) {
+        match value {
+            Value::String(s) => {
+                ui.add(
+                    egui::TextEdit::singleline(s)
+                        .font(self.text_font.clone())
+                        .desired_width(300.0),
+                );
+            }
+            Value::Integer(n) => {
+                ui.add(egui::DragValue::new(n));
+            }
+            Value::Float(n) => {
+                ui.add(egui::DragValue::new(n).speed(0.1));
+            }
+            Value::Boolean(b) => {
+                ui.checkbox(b, "");
+            }
+            Value::Datetime(datetime) => {
+                ui.label(egui::RichText::new(datetime.to_string()).font(self.text_font.clone()));
+            }
+            Value::Array(items) => {
+                ui.label(egui::RichText::new(format!("{} items", items.len())).weak());
+            }
+            Value::Table(table) => {
+                ui.label(egui::RichText::new(format!("{} keys", table.len())).weak());
+            }
+        }
+    }
+
+    /// Nested arrays and tables are shown below their row, one level deeper.
+    fn show_children(&self, ui: &mut Ui, value: &mut Value, id: Id, indent: f32) {
+        match value {
+            Value::Array(items) => self.show_items(ui, items, id, indent + 16.0),
+            Value::Table(table) => self.show_table(ui, table, id, indent + 16.0),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use std::ops::RangeInclusive;
+
+use egui::{Color32, Pos2, Rect, Sense, Stroke, StrokeKind, Ui, vec2};
+
+const PREVIEW_SIZE: f32 = 64.0;
+
+/// One drag value per component, labelled with `names`. Returns whether any changed.
+pub fn vector_edit(
+    ui: &mut Ui,
+    values: &mut [f64],
+    names: &[&str],
+    range: &RangeInclusive<f64>,
+) -> bool {
+    // Bounded components, like block bounds in 0..1, need finer steps.
+    let span = range.end() - range.start();
+    let speed = if span.is_finite() { span / 100.0 } else { 0.1 };
+    let mut changed = false;
+    for (value, name) in values.iter_mut().zip(names) {
+        ui.label(egui::RichText::new(*name).weak());
+        changed |= ui
+            .add(
+                egui::DragValue::new(value)
+                    .range(range.clone())
+                    .speed(speed)
+                    .max_decimals(4),
+            )
+            .changed();
+    }
+    changed
+}
+
+/// Top (X/Z) and side (X/Y) wireframes of an `aabb` value, drawn inside `range`, or
+/// inside the unit block when the range is unbounded.
+pub fn aabb_preview(ui: &mut Ui, value: &[f64; 6], range: &RangeInclusive<f64>) {
+    let [min_x, min_y, min_z, max_x, max_y, max_z] = *value;
+    let low = if range.start().is_finite() {
+        *range.start()
+    } else {
+        value.iter().copied().fold(0.0, f64::min)
+    };
+    let high = if range.end().is_finite() {
+        *range.end()
+    } else {
+        value.iter().copied().fold(1.0, f64::max)
+    };
+    let span = (high - low).max(f64::EPSILON);
+
+    ui.vertical(|ui| {
+        ui.label(egui::RichText::new("top").weak().small());
+        draw_view(ui, (min_x, min_z), (max_x, max_z), low, span, false);
+    });
+    ui.vertical(|ui| {
+        ui.label(egui::RichText::new("side").weak().small());
+        draw_view(ui, (min_x, min_y), (max_x, max_y), low, span, true);
+    });
+}
+
+/// Draws the frame of the allowed range and the box from `min` to `max` in it.
+/// `flip` puts the second axis upwards, as Y is in the side view.
+fn draw_view(ui: &mut Ui, min: (f64, f64), max: (f64, f64), low: f64, span: f64, flip: bool) {
+    let (frame, _) = ui.allocate_exact_size(vec2(PREVIEW_SIZE, PREVIEW_SIZE), Sense::hover());
+    let to_screen = |(u, v): (f64, f64)| {
+        let u = ((u - low) / span) as f32;
+        let v = ((v - low) / span) as f32;
+        let v = if flip { 1.0 - v } else { v };
+        Pos2::new(
+            frame.left() + u * frame.width(),
+            frame.top() + v * frame.height(),
+        )
+    };
+
+    let painter = ui.painter_at(frame.expand(1.0));
+    let visuals = ui.visuals();
+    painter.rect_filled(frame, 0.0, visuals.extreme_bg_color);
+    painter.rect_stroke(
+        frame,
+        0.0,
+        Stroke::new(1.0, visuals.weak_text_color()),
+        StrokeKind::Inside,
+    );
+
+    let shape = Rect::from_two_pos(to_screen(min), to_screen(max));
+    let selection = visuals.selection.bg_fill;
+    painter.rect_filled(
+        shape,
+        0.0,
+        Color32::from_rgba_unmultiplied(selection.r(), selection.g(), selection.b(), 60),
+    );
+    painter.rect_stroke(
+        shape,
+        0.0,
+        Stroke::new(1.5, visuals.selection.stroke.color),
+        StrokeKind::Middle,
+    );
+}
@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+
+use egui::{FontId, Ui};
+
+use crate::editor::form::{
+    form::{Field, FieldType, FieldValue},
+    options::OptionSets,
+};
+
+/// What a widget gets to know about the field it draws.
+pub struct WidgetContext<'a> {
+    /// Dotted path of the field, e.g. `data.color`. Unique within the form, so usable
+    /// as an id salt.
+    pub path: &'a str,
+    /// The field's label, or its key when it has none.
+    pub label: &'a str,
+    pub indent: f32,
+    pub label_font: FontId,
+    pub text_font: FontId,
+    pub description_font: FontId,
+    /// Whether descriptions go under the field. Otherwise the form shows them as the
+    /// field's tooltip.
+    pub inline_description: bool,
+    /// Option sets of the document, for widgets that offer choices.
+    pub options: &'a OptionSets,
+}
+
+/// A custom editor for fields, chosen by the schema's `widget = "<name>"` hint or by
+/// field type.
+pub trait FieldWidget {
+    /// Whether the widget can edit `field`. Fields it cannot edit fall back to the
+    /// built-in editor.
+    fn supports(&self, _field: &Field) -> bool {
+        true
+    }
+
+    /// Draws the label and editor of `field`.
+    fn show(&self, ui: &mut Ui, field: &mut Field, ctx: &WidgetContext<'_>);
+}
+
+/// The widgets a [`Form`](super::form_view::Form) can use in place of its built-in
+/// editors.
+///
+/// A field's `widget` hint takes precedence over a widget registered for its type. An
+/// unknown hint is ignored.
+pub struct WidgetRegistry {
+    by_name: HashMap<String, Box<dyn FieldWidget>>,
+    by_type: HashMap<FieldType, Box<dyn FieldWidget>>,
+}
+
+impl Default for WidgetRegistry {
+    /// A registry with the bundled widgets, `multiline` and `drag`.
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        registry.register("multiline", MultilineWidget);
+        registry.register("drag", DragWidget);
+        registry
+    }
+}
+
+impl WidgetRegistry {
+    pub fn empty() -> Self {
+        Self {
+            by_name: HashMap::new(),
+            by_type: HashMap::new(),
+        }
+    }
+
+    /// Makes `widget` available to fields with `widget = "<name>"`.
+    pub fn register(&mut self, name: &str, widget: impl FieldWidget + 'static) {
+        self.by_name.insert(name.to_owned(), Box::new(widget));
+    }
+
+    /// Uses `widget` for every field of `field_type` without a `widget` hint.
+    pub fn register_for_type(&mut self, field_type: FieldType, widget: impl FieldWidget + 'static) {
+        self.by_type.insert(field_type, Box::new(widget));
+    }
+
+    /// The widget that should draw `field`, if any.
+    pub fn find(&self, field: &Field) -> Option<&dyn FieldWidget> {
+        let by_name = field
+            .widget
+            .as_deref()
+            .and_then(|name| self.by_name.get(name));
+        let by_type = || self.by_type.get(&field.value.field_type());
+        by_name
+            .filter(|widget| widget.supports(field))
+            .or_else(|| by_type().filter(|widget| widget.supports(field)))
+            .map(Box::as_ref)
+    }
+}
+
+fn show_label(ui: &mut Ui, ctx: &WidgetContext<'_>) {
+    ui.horizontal(|ui| {
+        ui.add_space(ctx.indent);
+        ui.label(egui::RichText::new(ctx.label).font(ctx.label_font.clone()));
+    });
+}
+
+fn show_description(ui: &mut Ui, field: &Field, ctx: &WidgetContext<'_>) {
+    if let Some(description) = field
+        .description
+        .as_ref()
+        .filter(|_| ctx.inline_description)
+    {
+        ui.horizontal(|ui| {
+            ui.add_space(ctx.indent);
+            ui.label(
+                egui::RichText::new(description)
+                    .font(ctx.description_font.clone())
+                    .color(egui::Color32::GRAY),
+            );
+        });
+    }
+}
+
+/// `widget = "multiline"`: a text area for long strings.
+struct MultilineWidget;
+
+impl FieldWidget for MultilineWidget {
+    fn supports(&self, field: &Field) -> bool {
+        matches!(field.value, FieldValue::String { .. })
+    }
+
+    fn show(&self, ui: &mut Ui, field: &mut Field, ctx: &WidgetContext<'_>) {
+        let FieldValue::String { value, .. } = &mut field.value else {
+            return;
+        };
+        ui.add_space(4.0);
+        show_label(ui, ctx);
+        ui.horizontal(|ui| {
+            ui.add_space(ctx.indent);
+            ui.add(
+                egui::TextEdit::multiline(value)
+                    .font(ctx.text_font.clone())
+                    .desired_rows(4)
+                    .desired_width(f32::INFINITY),
+            );
+        });
+        show_description(ui, field, ctx);
+        ui.add_space(4.0);
+    }
+}
+
+/// `widget = "drag"`: an unbounded drag value for numbers, instead of the 0-100 slider.
+struct DragWidget;
+
+impl FieldWidget for DragWidget {
+    fn supports(&self, field: &Field) -> bool {
+        matches!(
+            field.value,
+            FieldValue::Integer { .. } | FieldValue::Float { .. }
+        )
+    }
+
+    fn show(&self, ui: &mut Ui, field: &mut Field, ctx: &WidgetContext<'_>) {
+        ui.add_space(4.0);
+        ui.horizontal(|ui| {
+            ui.add_space(ctx.indent);
+            ui.label(egui::RichText::new(ctx.label).font(ctx.label_font.clone()));
+            match &mut field.value {
+                FieldValue::Integer { value, .. } => {
+                    ui.add(egui::DragValue::new(value));
+                }
+                FieldValue::Float { value, .. } => {
+                    ui.add(egui::DragValue::new(value).speed(0.1));
+                }
+                _ => {}
+            }
+        });
+        show_description(ui, field, ctx);
+        ui.add_space(4.0);
+    }
+}
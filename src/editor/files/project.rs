@@ -4,6 +4,8 @@ use std::{
     path::{Path, PathBuf},
 };
 
+use crate::editor::form::options::MANIFEST_FILE;
+
 #[derive(Debug)]
 pub enum ProjectError {
     InvalidPath,
@@ -45,6 +47,13 @@ impl fmt::Display for File {
 }
 
 impl File {
+    /// Whether the file can be opened as a form document.
+    pub fn is_document(&self) -> bool {
+        !self.is_folder
+            && self.path.extension().is_some_and(|ext| ext == "toml")
+            && self.name != MANIFEST_FILE
+    }
+
     pub fn get_file(&self, id: i32) -> Option<&File> {
         if self.id == id {
             return Some(self);
@@ -99,6 +108,25 @@ impl Project {
         self.files.get_file(id)
     }
 
+    /// Paths of every document in the project, leaving out the `schemas` folder.
+    pub fn document_paths(&self) -> Vec<PathBuf> {
+        fn collect(file: &File, schemas: &Path, paths: &mut Vec<PathBuf>) {
+            if file.path == schemas {
+                return;
+            }
+            if file.is_document() {
+                paths.push(file.path.clone());
+            }
+            for child in &file.children {
+                collect(child, schemas, paths);
+            }
+        }
+
+        let mut paths = Vec::new();
+        collect(&self.files, &self.path.join("schemas"), &mut paths);
+        paths
+    }
+
     pub fn load(mut self) -> Self {
         if let Err(e) = self.load_files() {
             eprintln!("Error loading files: {:?}", e);
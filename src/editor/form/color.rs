@@ -0,0 +1,147 @@
+use serde::{Deserialize, Serialize};
+
+/// The stored value of a color field.
+///
+/// The schema's value decides the storage: a string keeps the color as `"#rrggbb"` /
+/// `"#rrggbbaa"`, an integer as `0xRRGGBB` / `0xAARRGGBB`, the layout the game reads
+/// tint colors in.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ColorValue {
+    Integer(i64),
+    Hex(String),
+}
+
+/// An sRGB color with straight (unmultiplied) alpha.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgba {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Rgba {
+    pub const BLACK: Self = Self {
+        r: 0,
+        g: 0,
+        b: 0,
+        a: 255,
+    };
+
+    /// Parses `#rrggbb`, or `#rrggbbaa` when `alpha` is set. The `#` is optional.
+    ///
+    /// # Errors
+    /// Fails with a message for the user if `text` is not a color of that form.
+    pub fn from_hex(text: &str, alpha: bool) -> Result<Self, String> {
+        let digits = text.trim().trim_start_matches('#');
+        let expected = if alpha { 8 } else { 6 };
+        if digits.len() != expected || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(if alpha {
+                format!("`{text}` is not a color of the form #rrggbbaa")
+            } else {
+                format!("`{text}` is not a color of the form #rrggbb")
+            });
+        }
+        let byte = |at: usize| {
+            digits
+                .get(at..at + 2)
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .unwrap_or(255)
+        };
+        Ok(Self {
+            r: byte(0),
+            g: byte(2),
+            b: byte(4),
+            a: if alpha { byte(6) } else { 255 },
+        })
+    }
+
+    /// Reads `0xRRGGBB`, or `0xAARRGGBB` when `alpha` is set.
+    ///
+    /// # Errors
+    /// Fails if `value` has bits outside those channels.
+    pub fn from_integer(value: i64, alpha: bool) -> Result<Self, String> {
+        let max: i64 = if alpha { 0xFFFF_FFFF } else { 0xFF_FFFF };
+        if !(0..=max).contains(&value) {
+            return Err(format!("{value} is not a color between 0 and {max:#X}"));
+        }
+        let [a, r, g, b] = (value as u32).to_be_bytes();
+        Ok(Self {
+            r,
+            g,
+            b,
+            a: if alpha { a } else { 255 },
+        })
+    }
+
+    pub fn to_hex(self, alpha: bool) -> String {
+        if alpha {
+            format!("#{:02x}{:02x}{:02x}{:02x}", self.r, self.g, self.b, self.a)
+        } else {
+            format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+        }
+    }
+
+    pub fn to_integer(self, alpha: bool) -> i64 {
+        let a = if alpha { self.a } else { 0 };
+        i64::from(u32::from_be_bytes([a, self.r, self.g, self.b]))
+    }
+}
+
+impl ColorValue {
+    /// The color this value stands for.
+    ///
+    /// # Errors
+    /// Fails with a message for the user if the stored value is not a valid color.
+    pub fn rgba(&self, alpha: bool) -> Result<Rgba, String> {
+        match self {
+            Self::Hex(text) => Rgba::from_hex(text, alpha),
+            Self::Integer(value) => Rgba::from_integer(*value, alpha),
+        }
+    }
+
+    /// Stores `color`, keeping the current representation.
+    pub fn set(&mut self, color: Rgba, alpha: bool) {
+        *self = match self {
+            Self::Hex(_) => Self::Hex(color.to_hex(alpha)),
+            Self::Integer(_) => Self::Integer(color.to_integer(alpha)),
+        };
+    }
+
+    /// The value as shown in text boxes: the hex string, or the integer in hex.
+    pub fn to_text(&self, alpha: bool) -> String {
+        match self {
+            Self::Hex(text) => text.clone(),
+            Self::Integer(value) => {
+                let digits = if alpha { 8 } else { 6 };
+                format!("0x{value:0digits$X}")
+            }
+        }
+    }
+
+    /// Parses text typed for a value of this representation: `#rrggbb` for hex colors,
+    /// `0xRRGGBB` or a decimal number for integer colors.
+    ///
+    /// # Errors
+    /// Fails with a message for the user if `text` is not a valid color.
+    pub fn parse_like(&self, text: &str, alpha: bool) -> Result<Self, String> {
+        match self {
+            Self::Hex(_) => Rgba::from_hex(text, alpha).map(|color| Self::Hex(color.to_hex(alpha))),
+            Self::Integer(_) => {
+                let trimmed = text.trim();
+                let parsed = match trimmed
+                    .strip_prefix("0x")
+                    .or_else(|| trimmed.strip_prefix("0X"))
+                    .or_else(|| trimmed.strip_prefix('#'))
+                {
+                    Some(digits) => i64::from_str_radix(digits, 16),
+                    None => trimmed.parse(),
+                };
+                let value =
+                    parsed.map_err(|err| format!("`{text}` is not a color number: {err}"))?;
+                Rgba::from_integer(value, alpha).map(|_| Self::Integer(value))
+            }
+        }
+    }
+}
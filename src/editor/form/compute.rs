@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+use crate::editor::form::{
+    conditions::resolve_field,
+    expr::{Expr, Value},
+    form::{Document, Field, FieldValue},
+};
+
+/// Result of a field's `compute` formula for the current frame.
+pub struct ComputedState {
+    pub formula: String,
+    /// Read-only fields always hold the computed value; suggested ones only offer it.
+    pub readonly: bool,
+    pub suggestion: Option<Value>,
+    pub error: Option<String>,
+}
+
+/// The computed fields of a document, keyed by field path.
+#[derive(Default)]
+pub struct Computations {
+    states: HashMap<String, ComputedState>,
+}
+
+struct Formula {
+    path: String,
+    parent: String,
+    source: String,
+    readonly: bool,
+}
+
+impl Computations {
+    /// Recomputes every attached `compute` field of `document`. Read-only results are
+    /// written into the document, repeating until fields computed from other computed
+    /// fields settle.
+    pub fn apply(document: &mut Document) -> Self {
+        let mut formulas = Vec::new();
+        for path in document.leaf_paths() {
+            let Some(field) = document.get(&path) else {
+                continue;
+            };
+            let (Some(source), false) = (&field.compute, field.detached.unwrap_or(false)) else {
+                continue;
+            };
+            formulas.push(Formula {
+                parent: path
+                    .rsplit_once('.')
+                    .map(|(p, _)| p.to_owned())
+                    .unwrap_or_default(),
+                readonly: !field.suggest.unwrap_or(false),
+                source: source.clone(),
+                path,
+            });
+        }
+
+        let mut computations = Self::default();
+        for _ in 0..=formulas.len() {
+            let mut changed = false;
+            for formula in &formulas {
+                let result = Expr::parse(&formula.source).and_then(|expr| {
+                    expr.eval(&|name: &str| resolve_field(document, &formula.parent, name))
+                });
+                let (suggestion, error) = match result {
+                    Ok(value) => (Some(value), None),
+                    Err(err) => (None, Some(err.to_string())),
+                };
+
+                if let (true, Some(value), Some(field)) = (
+                    formula.readonly,
+                    &suggestion,
+                    document.get_mut(&formula.path),
+                ) {
+                    changed |= assign(&mut field.value, value);
+                }
+
+                computations.states.insert(
+                    formula.path.clone(),
+                    ComputedState {
+                        formula: formula.source.clone(),
+                        readonly: formula.readonly,
+                        suggestion,
+                        error,
+                    },
+                );
+            }
+            if !changed {
+                break;
+            }
+        }
+        computations
+    }
+
+    pub fn get(&self, path: &str) -> Option<&ComputedState> {
+        self.states.get(path)
+    }
+}
+
+/// Stores `value` in a scalar field, converting it to the field type. Returns whether the
+/// field changed.
+pub fn assign(field: &mut FieldValue, value: &Value) -> bool {
+    match (field, value) {
+        (
+            FieldValue::String { value: target, .. }
+            | FieldValue::Image { value: target, .. }
+            | FieldValue::Enum { value: target, .. },
+            value,
+        ) => {
+            let text = value.to_text();
+            let changed = *target != text;
+            *target = text;
+            changed
+        }
+        (FieldValue::Float { value: target, .. }, Value::Number(n)) => {
+            let changed = *target != *n;
+            *target = *n;
+            changed
+        }
+        (FieldValue::Integer { value: target, .. }, Value::Number(n)) => {
+            let n = n.round() as i64;
+            let changed = *target != n;
+            *target = n;
+            changed
+        }
+        (FieldValue::Boolean { value: target, .. }, value) => {
+            let changed = *target != value.truthy();
+            *target = value.truthy();
+            changed
+        }
+        (
+            FieldValue::Color {
+                value: target,
+                alpha,
+                ..
+            },
+            value,
+        ) => {
+            // Values that are not colors leave the field as it is.
+            let Ok(color) = target.parse_like(&value.to_text(), alpha.unwrap_or(false)) else {
+                return false;
+            };
+            let changed = *target != color;
+            *target = color;
+            changed
+        }
+        _ => false,
+    }
+}
+
+/// Whether `field` already holds `value`, after conversion to the field type.
+pub fn holds(field: &Field, value: &Value) -> bool {
+    let mut probe = field.value.clone();
+    !assign(&mut probe, value)
+}
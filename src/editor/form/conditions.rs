@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+
+use indexmap::IndexMap;
+
+use crate::editor::form::{
+    expr::{Expr, Value},
+    form::{Document, Field, FieldValue, join_path},
+};
+
+/// Visibility and editability of a field, computed from its `visible_if` / `enabled_if`.
+#[derive(Clone)]
+pub struct FieldState {
+    pub visible: bool,
+    pub enabled: bool,
+    pub error: Option<String>,
+}
+
+impl Default for FieldState {
+    fn default() -> Self {
+        Self {
+            visible: true,
+            enabled: true,
+            error: None,
+        }
+    }
+}
+
+/// The evaluated conditions of every field of a document, keyed by field path.
+#[derive(Default)]
+pub struct Conditions {
+    states: HashMap<String, FieldState>,
+}
+
+impl Conditions {
+    /// Evaluates every condition of `document`. A hidden or disabled table hides or
+    /// disables all of its children.
+    pub fn evaluate(document: &Document) -> Self {
+        let mut conditions = Self::default();
+        conditions.evaluate_fields(document, &document.fields, "", &FieldState::default());
+        conditions
+    }
+
+    fn evaluate_fields(
+        &mut self,
+        document: &Document,
+        fields: &IndexMap<String, Field>,
+        parent: &str,
+        parent_state: &FieldState,
+    ) {
+        for (key, field) in fields {
+            let path = join_path(parent, key);
+            let mut state = FieldState {
+                visible: parent_state.visible,
+                enabled: parent_state.enabled,
+                error: None,
+            };
+
+            if let Some(source) = &field.visible_if {
+                match Self::check(document, parent, source) {
+                    Ok(visible) => state.visible &= visible,
+                    Err(err) => state.error = Some(format!("visible_if: {err}")),
+                }
+            }
+            if let Some(source) = &field.enabled_if {
+                match Self::check(document, parent, source) {
+                    Ok(enabled) => state.enabled &= enabled,
+                    Err(err) => state.error = Some(format!("enabled_if: {err}")),
+                }
+            }
+
+            if let FieldValue::Table { children } = &field.value {
+                self.evaluate_fields(document, children, &path, &state);
+            }
+            self.states.insert(path, state);
+        }
+    }
+
+    /// Evaluates `source` for a field inside `parent`. Names are looked up as siblings
+    /// first and as absolute paths second.
+    fn check(document: &Document, parent: &str, source: &str) -> Result<bool, String> {
+        let expr = Expr::parse(source).map_err(|err| err.to_string())?;
+        let resolve = |name: &str| resolve_field(document, parent, name);
+        expr.eval(&resolve)
+            .map(|value| value.truthy())
+            .map_err(|err| err.to_string())
+    }
+
+    pub fn is_visible(&self, path: &str) -> bool {
+        self.states.get(path).is_none_or(|s| s.visible)
+    }
+
+    pub fn is_enabled(&self, path: &str) -> bool {
+        self.states.get(path).is_none_or(|s| s.enabled)
+    }
+
+    /// The error of a condition that could not be evaluated. Such conditions are ignored.
+    pub fn error(&self, path: &str) -> Option<&str> {
+        self.states.get(path)?.error.as_deref()
+    }
+}
+
+/// Reads field `name` as seen from a field inside `parent`.
+pub fn resolve_field(document: &Document, parent: &str, name: &str) -> Option<Value> {
+    document
+        .get(&join_path(parent, name))
+        .or_else(|| document.get(name))
+        .and_then(|field| Value::from_field(&field.value))
+}
@@ -0,0 +1,304 @@
+use toml::{Table, Value};
+
+use crate::editor::form::{
+    color::Rgba,
+    form::{Field, FieldType},
+};
+
+/// A field converted to another type, as offered in the field's context menu.
+pub struct Conversion {
+    pub to: FieldType,
+    /// The converted field, or why the value cannot be converted.
+    pub field: Result<Field, String>,
+    /// Whether converting back gives the original value.
+    pub lossless: bool,
+    /// Old and new value, e.g. `2.6 -> 3`.
+    pub description: String,
+}
+
+/// Every conversion the schema allows for `field`, excluding its current type.
+pub fn conversions(field: &Field) -> Vec<Conversion> {
+    let current = field.value.field_type();
+    field
+        .convert
+        .as_ref()
+        .map(|targets| targets.types())
+        .unwrap_or_default()
+        .iter()
+        .filter(|to| **to != current)
+        .map(|to| convert(field, *to))
+        .collect()
+}
+
+/// Converts `field` to `to`, checking whether the conversion loses information.
+pub fn convert(field: &Field, to: FieldType) -> Conversion {
+    let failed = |reason: String| Conversion {
+        to,
+        field: Err(reason.clone()),
+        lossless: false,
+        description: reason,
+    };
+
+    let from = field.value.field_type();
+    let Some(mut table) = Value::try_from(field)
+        .ok()
+        .and_then(|value| value.as_table().cloned())
+    else {
+        return failed("field cannot be serialized".to_owned());
+    };
+    let original = table.get("value").cloned();
+
+    let description = match convert_field(&mut table, to) {
+        Ok(description) => description,
+        Err(err) => return failed(err),
+    };
+
+    // Scalars wrapped in an array can always be unwrapped again.
+    let lossless = to == FieldType::Array || {
+        let mut back = table.clone();
+        convert_field(&mut back, from).is_ok() && back.get("value") == original.as_ref()
+    };
+
+    match Value::Table(table).try_into::<Field>() {
+        Ok(converted) => Conversion {
+            to,
+            field: Ok(converted),
+            lossless,
+            description,
+        },
+        Err(err) => failed(err.to_string()),
+    }
+}
+
+/// Converts the stored value of a raw field table to `to`, updating its `type`.
+///
+/// # Errors
+/// Fails if the field has no value or the value has no equivalent of type `to`.
+pub fn convert_field(field: &mut Table, to: FieldType) -> Result<String, String> {
+    let Some(value) = field.get("value").cloned() else {
+        return Err("field has no scalar value".to_owned());
+    };
+
+    if to == FieldType::Array {
+        let array_type = match value {
+            Value::String(_) => "strings",
+            Value::Integer(_) => "integers",
+            Value::Float(_) => "floats",
+            _ => "mixed",
+        };
+        field.remove("value");
+        field.remove("default");
+        field.remove("options");
+        field.insert("type".to_owned(), Value::String("array".to_owned()));
+        field.insert(
+            "array_type".to_owned(),
+            Value::String(array_type.to_owned()),
+        );
+        field.insert("items".to_owned(), Value::Array(vec![value.clone()]));
+        return Ok(format!("{value} -> [{value}]"));
+    }
+
+    let converted =
+        convert_value(&value, to).ok_or_else(|| format!("cannot convert {value} to {to:?}"))?;
+    match field.get("default").and_then(|d| convert_value(d, to)) {
+        Some(default) => field.insert("default".to_owned(), default),
+        None => field.remove("default"),
+    };
+    if to == FieldType::Enum {
+        // The current value must stay selectable.
+        let options = field
+            .entry("options")
+            .or_insert_with(|| Value::Array(Vec::new()));
+        if let Some(options) = options.as_array_mut() {
+            if !options.contains(&converted) {
+                options.push(converted.clone());
+            }
+        }
+    } else {
+        field.remove("options");
+    }
+    if to == FieldType::Color {
+        // Colors with an alpha channel only parse as such.
+        let alpha = match &converted {
+            Value::String(s) => s.trim_start_matches('#').len() == 8,
+            Value::Integer(i) => *i > 0xFF_FFFF,
+            _ => false,
+        };
+        if alpha {
+            field.insert("alpha".to_owned(), Value::Boolean(true));
+        }
+    } else {
+        field.remove("alpha");
+    }
+    field.insert("type".to_owned(), Value::String(type_name(to).to_owned()));
+    field.insert("value".to_owned(), converted.clone());
+    Ok(format!("{value} -> {converted}"))
+}
+
+/// Converts a scalar TOML value to the representation used by fields of type `to`.
+pub fn convert_value(value: &Value, to: FieldType) -> Option<Value> {
+    let text = |value: &Value| match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    match (to, value) {
+        (FieldType::Integer, Value::Integer(_))
+        | (FieldType::Float, Value::Float(_))
+        | (FieldType::Boolean, Value::Boolean(_))
+        | (FieldType::String | FieldType::Image | FieldType::Enum, Value::String(_)) => {
+            Some(value.clone())
+        }
+        (FieldType::Integer, Value::Float(f)) => Some(Value::Integer(f.round() as i64)),
+        (FieldType::Integer, Value::Boolean(b)) => Some(Value::Integer((*b).into())),
+        (FieldType::Integer, Value::String(s)) => s.trim().parse().ok().map(Value::Integer),
+        (FieldType::Float, Value::Integer(i)) => Some(Value::Float(*i as f64)),
+        (FieldType::Float, Value::Boolean(b)) => Some(Value::Float(if *b { 1.0 } else { 0.0 })),
+        (FieldType::Float, Value::String(s)) => s.trim().parse().ok().map(Value::Float),
+        (FieldType::Boolean, Value::Integer(i)) => Some(Value::Boolean(*i != 0)),
+        (FieldType::Boolean, Value::Float(f)) => Some(Value::Boolean(*f != 0.0)),
+        (FieldType::Boolean, Value::String(s)) => s.trim().parse().ok().map(Value::Boolean),
+        (FieldType::String | FieldType::Image | FieldType::Enum, value) => {
+            Some(Value::String(text(value)))
+        }
+        (FieldType::Color, Value::String(s)) => {
+            let alpha = s.trim().trim_start_matches('#').len() == 8;
+            Rgba::from_hex(s, alpha)
+                .ok()
+                .map(|color| Value::String(color.to_hex(alpha)))
+        }
+        (FieldType::Color, Value::Integer(i)) => {
+            Rgba::from_integer(*i, true).is_ok().then(|| value.clone())
+        }
+        _ => None,
+    }
+}
+
+pub fn type_name(field_type: FieldType) -> &'static str {
+    match field_type {
+        FieldType::String => "string",
+        FieldType::Integer => "integer",
+        FieldType::Float => "float",
+        FieldType::Boolean => "boolean",
+        FieldType::Image => "image",
+        FieldType::Table => "table",
+        FieldType::Array => "array",
+        FieldType::Enum => "enum",
+        FieldType::Color => "color",
+        FieldType::Vec2 => "vec2",
+        FieldType::Vec3 => "vec3",
+        FieldType::Aabb => "aabb",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use toml::{Table, Value};
+
+    use super::{conversions, convert, convert_field, convert_value};
+    use crate::editor::form::form::{Field, FieldType, FieldValue};
+
+    fn field(text: &str) -> Field {
+        toml::from_str(text).expect("valid field")
+    }
+
+    #[test]
+    fn converts_scalar_values() {
+        let cases = [
+            (
+                Value::Float(2.6),
+                FieldType::Integer,
+                Some(Value::Integer(3)),
+            ),
+            (
+                Value::String(" 7 ".into()),
+                FieldType::Integer,
+                Some(Value::Integer(7)),
+            ),
+            (
+                Value::Integer(0),
+                FieldType::Boolean,
+                Some(Value::Boolean(false)),
+            ),
+            (
+                Value::Boolean(true),
+                FieldType::Float,
+                Some(Value::Float(1.0)),
+            ),
+            (
+                Value::Float(1.5),
+                FieldType::String,
+                Some(Value::String("1.5".into())),
+            ),
+            (
+                Value::String("#ABCDEF".into()),
+                FieldType::Color,
+                Some(Value::String("#abcdef".into())),
+            ),
+            (Value::String("stone".into()), FieldType::Integer, None),
+            (Value::String("stone".into()), FieldType::Vec2, None),
+        ];
+        for (value, to, expected) in cases {
+            assert_eq!(convert_value(&value, to), expected, "{value} to {to:?}");
+        }
+    }
+
+    #[test]
+    fn offers_listed_conversions_and_checks_losses() {
+        let hardness =
+            field("type = \"float\"\nvalue = 2.6\nconvert = [\"float\", \"integer\", \"string\"]");
+        let offered: Vec<_> = conversions(&hardness).iter().map(|c| c.to).collect();
+        assert_eq!(
+            offered,
+            [FieldType::Integer, FieldType::String],
+            "current type left out"
+        );
+
+        let to_integer = convert(&hardness, FieldType::Integer);
+        assert!(!to_integer.lossless, "rounding loses the fraction");
+        assert_eq!(to_integer.description, "2.6 -> 3", "description");
+        assert!(
+            matches!(
+                to_integer.field.map(|f| f.value),
+                Ok(FieldValue::Integer { value: 3, .. })
+            ),
+            "converted field"
+        );
+        assert!(
+            convert(&hardness, FieldType::String).lossless,
+            "text parses back"
+        );
+        assert!(
+            convert(&hardness, FieldType::Boolean).field.is_ok(),
+            "unlisted types can still be converted explicitly"
+        );
+    }
+
+    #[test]
+    fn rewrites_field_tables() {
+        let mut table: Table =
+            toml::from_str("type = \"string\"\nvalue = \"b\"\ndefault = \"a\"").expect("valid");
+        convert_field(&mut table, FieldType::Enum).expect("strings become enums");
+        assert_eq!(
+            table.get("type").and_then(Value::as_str),
+            Some("enum"),
+            "type"
+        );
+        assert_eq!(
+            table.get("options").and_then(Value::as_array),
+            Some(&vec![Value::String("b".into())]),
+            "the value stays selectable"
+        );
+
+        convert_field(&mut table, FieldType::Array).expect("anything can be wrapped");
+        assert_eq!(
+            table.get("array_type").and_then(Value::as_str),
+            Some("strings"),
+            "array type"
+        );
+        assert!(!table.contains_key("options"), "enum settings dropped");
+        assert!(
+            convert_field(&mut table, FieldType::String).is_err(),
+            "arrays have no scalar value"
+        );
+    }
+}
@@ -0,0 +1,287 @@
+use std::{path::Path, process::Command};
+
+use indexmap::IndexMap;
+
+use crate::editor::form::form::{Document, Field, FieldValue};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// A field that differs between two versions of a document.
+#[derive(Debug, Clone)]
+pub struct FieldChange {
+    pub path: String,
+    pub kind: ChangeKind,
+    /// The value in the base version, as TOML.
+    pub old: Option<String>,
+    /// The value in the edited version, as TOML.
+    pub new: Option<String>,
+}
+
+/// Field-level differences from `base` to `edited`, in document order. Fields whose
+/// value is equal but whose settings (label, options, ...) differ count as changed.
+pub fn diff(base: &Document, edited: &Document) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+    let edited_paths = edited.leaf_paths();
+    let base_paths = base.leaf_paths();
+
+    for path in &edited_paths {
+        let new = edited.get(path);
+        match (base.get(path), new) {
+            (Some(old), Some(new)) if is_table(old) => {
+                // A table replaced by a leaf field.
+                changes.push(FieldChange {
+                    path: path.clone(),
+                    kind: ChangeKind::Changed,
+                    old: Some("table".to_owned()),
+                    new: Some(display(new, None)),
+                });
+            }
+            (Some(old), Some(new)) => {
+                let (old_table, new_table) = (field_table(old), field_table(new));
+                if old_table != new_table {
+                    let (old_text, new_text) = (display(old, None), display(new, None));
+                    let (old_text, new_text) = if old_text == new_text {
+                        (display(old, old_table), display(new, new_table))
+                    } else {
+                        (old_text, new_text)
+                    };
+                    changes.push(FieldChange {
+                        path: path.clone(),
+                        kind: ChangeKind::Changed,
+                        old: Some(old_text),
+                        new: Some(new_text),
+                    });
+                }
+            }
+            (None, Some(new)) => changes.push(FieldChange {
+                path: path.clone(),
+                kind: ChangeKind::Added,
+                old: None,
+                new: Some(display(new, None)),
+            }),
+            (_, None) => {}
+        }
+    }
+
+    for path in base_paths {
+        if edited_paths.contains(&path) {
+            continue;
+        }
+        let Some(old) = base.get(&path) else {
+            continue;
+        };
+        // A leaf turned into a table shows up through the table's own leaves.
+        if edited.get(&path).is_some_and(is_table) {
+            continue;
+        }
+        changes.push(FieldChange {
+            old: Some(display(old, None)),
+            path,
+            kind: ChangeKind::Removed,
+            new: None,
+        });
+    }
+    changes
+}
+
+/// Undoes `change` in `edited`, taking the field back from `base`.
+pub fn revert(edited: &mut Document, base: &Document, change: &FieldChange) {
+    match change.kind {
+        ChangeKind::Added => {
+            let (parent, key) = split_path(&change.path);
+            if let Some(children) = children_mut(edited, parent) {
+                children.shift_remove(key);
+            }
+        }
+        ChangeKind::Changed | ChangeKind::Removed => restore(edited, base, &change.path),
+    }
+}
+
+/// Copies the field at `path` from `base`, recreating missing parent tables.
+fn restore(edited: &mut Document, base: &Document, path: &str) {
+    let (parent, key) = split_path(path);
+    let Some(field) = base.get(path).cloned() else {
+        return;
+    };
+    if !parent.is_empty() && children_mut(edited, parent).is_none() {
+        restore(edited, base, parent);
+        return;
+    }
+    let index = children(base, parent).and_then(|c| c.get_index_of(key));
+    let Some(children) = children_mut(edited, parent) else {
+        return;
+    };
+    if let Some(current) = children.get_mut(key) {
+        *current = field;
+    } else if let Some(index) = index.filter(|index| *index <= children.len()) {
+        children.shift_insert(index, key.to_owned(), field);
+    } else {
+        children.insert(key.to_owned(), field);
+    }
+}
+
+/// Reads the version of `path` committed in the HEAD of its git repository.
+///
+/// # Errors
+/// Fails if git is not available, the file is not in a repository, or HEAD does not
+/// have it.
+pub fn git_head_text(path: &Path) -> Result<String, String> {
+    let dir = path.parent().ok_or("the file has no folder")?;
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or("the file name is not valid UTF-8")?;
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .arg("show")
+        .arg(format!("HEAD:./{name}"))
+        .output()
+        .map_err(|err| format!("could not run git: {err}"))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_owned());
+    }
+    String::from_utf8(output.stdout).map_err(|err| err.to_string())
+}
+
+fn is_table(field: &Field) -> bool {
+    matches!(field.value, FieldValue::Table { .. })
+}
+
+fn field_table(field: &Field) -> Option<toml::Table> {
+    toml::Table::try_from(field).ok()
+}
+
+/// The value of a field as TOML, or its whole definition when `table` is given.
+fn display(field: &Field, table: Option<toml::Table>) -> String {
+    if let Some(table) = table {
+        return toml::Value::Table(table).to_string();
+    }
+    let table = field_table(field).unwrap_or_default();
+    table
+        .get("value")
+        .or_else(|| table.get("items"))
+        .map_or_else(
+            || format!("{:?}", field.value.field_type()),
+            ToString::to_string,
+        )
+}
+
+fn split_path(path: &str) -> (&str, &str) {
+    path.rsplit_once('.').unwrap_or(("", path))
+}
+
+fn children<'a>(document: &'a Document, parent: &str) -> Option<&'a IndexMap<String, Field>> {
+    if parent.is_empty() {
+        return Some(&document.fields);
+    }
+    match &document.get(parent)?.value {
+        FieldValue::Table { children } => Some(children),
+        _ => None,
+    }
+}
+
+fn children_mut<'a>(
+    document: &'a mut Document,
+    parent: &str,
+) -> Option<&'a mut IndexMap<String, Field>> {
+    if parent.is_empty() {
+        return Some(&mut document.fields);
+    }
+    match &mut document.get_mut(parent)?.value {
+        FieldValue::Table { children } => Some(children),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChangeKind, diff, revert};
+    use crate::editor::form::form::Document;
+
+    const BASE: &str = r#"
+[data]
+type = "table"
+name = { type = "string", value = "stone" }
+hardness = { type = "float", value = 1.5 }
+light = { type = "integer", value = 0 }
+"#;
+
+    fn document(text: &str) -> Document {
+        Document::from_toml(text).expect("valid document")
+    }
+
+    fn edited() -> Document {
+        document(
+            &BASE
+                .replace("1.5 }", "2.0 }")
+                .replace("light = { type = \"integer\", value = 0 }\n", "")
+                .replace(
+                    "\"stone\" }",
+                    "\"stone\", label = \"Name\" }\nsolid = { type = \"boolean\", value = true }",
+                ),
+        )
+    }
+
+    #[test]
+    fn lists_changes_in_document_order() {
+        let changes = diff(&document(BASE), &edited());
+        let summary: Vec<_> = changes
+            .iter()
+            .map(|c| (c.path.as_str(), c.kind, c.old.as_deref(), c.new.as_deref()))
+            .collect();
+        assert_eq!(
+            summary.first().map(|c| (c.0, c.1)),
+            Some(("data.name", ChangeKind::Changed)),
+            "settings changes count: {summary:?}"
+        );
+        assert_eq!(
+            summary.get(1..),
+            Some(
+                [
+                    ("data.solid", ChangeKind::Added, None, Some("true")),
+                    (
+                        "data.hardness",
+                        ChangeKind::Changed,
+                        Some("1.5"),
+                        Some("2.0")
+                    ),
+                    ("data.light", ChangeKind::Removed, Some("0"), None),
+                ]
+                .as_slice()
+            ),
+            "added, changed and removed fields"
+        );
+        assert!(diff(&edited(), &edited()).is_empty(), "no changes");
+    }
+
+    #[test]
+    fn reverts_each_change() {
+        let base = document(BASE);
+        let mut edited = edited();
+        for change in diff(&base, &edited) {
+            revert(&mut edited, &base, &change);
+        }
+        assert!(diff(&base, &edited).is_empty(), "everything reverted");
+        assert_eq!(
+            edited.leaf_paths(),
+            base.leaf_paths(),
+            "removed fields go back to their place"
+        );
+    }
+
+    #[test]
+    fn reverts_removed_tables() {
+        let base = document(BASE);
+        let mut edited = document("");
+        for change in diff(&base, &edited) {
+            revert(&mut edited, &base, &change);
+        }
+        assert!(diff(&base, &edited).is_empty(), "table recreated");
+    }
+}
@@ -0,0 +1,100 @@
+use indexmap::IndexMap;
+use toml::{Table, Value};
+
+use crate::editor::form::{
+    color::ColorValue,
+    conditions::Conditions,
+    form::{ArrayValue, Document, Field, FieldValue, join_path},
+};
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExportOptions {
+    /// Leave out fields whose `visible_if` is false.
+    pub omit_hidden: bool,
+    /// Leave out fields that hold their schema default, and tables left empty by that.
+    pub omit_defaults: bool,
+}
+
+/// Which fields an export leaves out.
+struct Filter<'a> {
+    conditions: Option<&'a Conditions>,
+    omit_defaults: bool,
+}
+
+impl Filter<'_> {
+    fn skips(&self, field: &Field, path: &str) -> bool {
+        // Unset fields are left to the game's default.
+        field.is_unset()
+            || (self.omit_defaults && field.value.is_default() == Some(true))
+            || self.conditions.is_some_and(|c| !c.is_visible(path))
+    }
+}
+
+/// Converts a document to plain TOML data, dropping labels, descriptions and other schema data.
+pub fn export_document(document: &Document, options: ExportOptions) -> Table {
+    let conditions = options.omit_hidden.then(|| Conditions::evaluate(document));
+    let filter = Filter {
+        conditions: conditions.as_ref(),
+        omit_defaults: options.omit_defaults,
+    };
+    export_fields(&document.fields, "", &filter)
+}
+
+fn export_fields(fields: &IndexMap<String, Field>, parent: &str, filter: &Filter<'_>) -> Table {
+    let mut table = Table::new();
+    for (key, field) in fields {
+        let path = join_path(parent, key);
+        if filter.skips(field, &path) {
+            continue;
+        }
+        let value = export_value(&field.value, &path, filter);
+        if filter.omit_defaults && value.as_table().is_some_and(Table::is_empty) {
+            continue;
+        }
+        table.insert(key.clone(), value);
+    }
+    table
+}
+
+fn export_value(value: &FieldValue, path: &str, filter: &Filter<'_>) -> Value {
+    match value {
+        FieldValue::String { value, .. }
+        | FieldValue::Image { value, .. }
+        | FieldValue::Enum { value, .. } => Value::String(value.clone()),
+        FieldValue::Float { value, .. } => Value::Float(*value),
+        FieldValue::Integer { value, .. } => Value::Integer(*value),
+        FieldValue::Boolean { value, .. } => Value::Boolean(*value),
+        FieldValue::Color { value, .. } => match value {
+            ColorValue::Hex(text) => Value::String(text.clone()),
+            ColorValue::Integer(value) => Value::Integer(*value),
+        },
+        FieldValue::Vec2 { value, .. } => floats(value),
+        FieldValue::Vec3 { value, .. } => floats(value),
+        FieldValue::Aabb { value, .. } => floats(value),
+        FieldValue::Table { children } => Value::Table(export_fields(children, path, filter)),
+        FieldValue::Array { value } => Value::Array(match value {
+            ArrayValue::Enums { items, .. } => items
+                .iter()
+                .map(|e| Value::String(e.value.clone()))
+                .collect(),
+            ArrayValue::Strings { items } => items.iter().cloned().map(Value::String).collect(),
+            ArrayValue::Floats { items } => items.iter().copied().map(Value::Float).collect(),
+            ArrayValue::Integers { items } => items.iter().copied().map(Value::Integer).collect(),
+            ArrayValue::Objects { items, .. } => items
+                .iter()
+                .map(|item| {
+                    let filter = Filter {
+                        conditions: None,
+                        omit_defaults: filter.omit_defaults,
+                    };
+                    Value::Table(export_fields(item, "", &filter))
+                })
+                .collect(),
+            ArrayValue::Mixed { items } => items.clone(),
+        }),
+    }
+}
+
+fn floats(values: &[f64]) -> Value {
+    Value::Array(values.iter().map(|v| Value::Float(*v)).collect())
+}
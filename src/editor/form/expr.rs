@@ -0,0 +1,664 @@
+use std::fmt;
+
+use crate::editor::form::form::FieldValue;
+
+/// Result of evaluating an expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    Number(f64),
+    String(String),
+}
+
+impl Value {
+    /// Reads the value of a scalar field. Tables and arrays have no expression value.
+    pub fn from_field(value: &FieldValue) -> Option<Self> {
+        match value {
+            FieldValue::Boolean { value, .. } => Some(Self::Bool(*value)),
+            FieldValue::Float { value, .. } => Some(Self::Number(*value)),
+            FieldValue::Integer { value, .. } => Some(Self::Number(*value as f64)),
+            FieldValue::String { value, .. }
+            | FieldValue::Image { value, .. }
+            | FieldValue::Enum { value, .. } => Some(Self::String(value.clone())),
+            FieldValue::Color { value, alpha, .. } => {
+                Some(Self::String(value.to_text(alpha.unwrap_or(false))))
+            }
+            _ => None,
+        }
+    }
+
+    /// The value as plain text, without quotes around strings.
+    pub fn to_text(&self) -> String {
+        match self {
+            Self::String(s) => s.clone(),
+            other => other.to_string(),
+        }
+    }
+
+    /// `false`, `0` and `""` are falsy, everything else is truthy.
+    pub fn truthy(&self) -> bool {
+        match self {
+            Self::Bool(b) => *b,
+            Self::Number(n) => *n != 0.0,
+            Self::String(s) => !s.is_empty(),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bool(b) => write!(f, "{b}"),
+            Self::Number(n) => write!(f, "{n}"),
+            Self::String(s) => write!(f, "{s:?}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExprError {
+    Parse(String),
+    UnknownField(String),
+    Type(String),
+}
+
+impl fmt::Display for ExprError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(msg) => write!(f, "syntax error: {msg}"),
+            Self::UnknownField(name) => write!(f, "unknown field `{name}`"),
+            Self::Type(msg) => write!(f, "type error: {msg}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    And,
+    Or,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// A parsed schema expression, e.g. `enable_furnace && data.hardness > 1` or
+/// `snake_case(data.name)`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Literal(Value),
+    Field(String),
+    Not(Box<Expr>),
+    Neg(Box<Expr>),
+    Binary(Box<Expr>, BinaryOp, Box<Expr>),
+    Call(String, Vec<Expr>),
+}
+
+impl Expr {
+    /// Parses an expression.
+    ///
+    /// # Errors
+    /// Fails if `source` is not a well formed expression.
+    pub fn parse(source: &str) -> Result<Self, ExprError> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.or()?;
+        match parser.peek() {
+            None => Ok(expr),
+            Some(token) => Err(ExprError::Parse(format!("unexpected `{token}`"))),
+        }
+    }
+
+    /// Evaluates the expression, reading field references through `resolve`.
+    ///
+    /// # Errors
+    /// Fails if a field cannot be resolved or operands have incompatible types.
+    pub fn eval(&self, resolve: &dyn Fn(&str) -> Option<Value>) -> Result<Value, ExprError> {
+        match self {
+            Self::Literal(value) => Ok(value.clone()),
+            Self::Field(name) => resolve(name).ok_or_else(|| ExprError::UnknownField(name.clone())),
+            Self::Not(inner) => Ok(Value::Bool(!inner.eval(resolve)?.truthy())),
+            Self::Neg(inner) => match inner.eval(resolve)? {
+                Value::Number(n) => Ok(Value::Number(-n)),
+                other => Err(ExprError::Type(format!("cannot negate {other}"))),
+            },
+            Self::Binary(lhs, op, rhs) => {
+                let lhs = lhs.eval(resolve)?;
+                match op {
+                    BinaryOp::And if !lhs.truthy() => Ok(Value::Bool(false)),
+                    BinaryOp::Or if lhs.truthy() => Ok(Value::Bool(true)),
+                    BinaryOp::And | BinaryOp::Or => Ok(Value::Bool(rhs.eval(resolve)?.truthy())),
+                    BinaryOp::Add
+                    | BinaryOp::Sub
+                    | BinaryOp::Mul
+                    | BinaryOp::Div
+                    | BinaryOp::Rem => arithmetic(&lhs, *op, &rhs.eval(resolve)?),
+                    _ => compare(&lhs, *op, &rhs.eval(resolve)?),
+                }
+            }
+            Self::Call(name, args) => {
+                let args = args
+                    .iter()
+                    .map(|arg| arg.eval(resolve))
+                    .collect::<Result<Vec<_>, _>>()?;
+                call(name, &args)
+            }
+        }
+    }
+
+    /// Names of every field the expression reads.
+    pub fn fields(&self) -> Vec<&str> {
+        let mut fields = Vec::new();
+        self.collect_fields(&mut fields);
+        fields
+    }
+
+    fn collect_fields<'a>(&'a self, fields: &mut Vec<&'a str>) {
+        match self {
+            Self::Literal(_) => {}
+            Self::Field(name) => fields.push(name),
+            Self::Not(inner) | Self::Neg(inner) => inner.collect_fields(fields),
+            Self::Binary(lhs, _, rhs) => {
+                lhs.collect_fields(fields);
+                rhs.collect_fields(fields);
+            }
+            Self::Call(_, args) => {
+                for arg in args {
+                    arg.collect_fields(fields);
+                }
+            }
+        }
+    }
+}
+
+fn compare(lhs: &Value, op: BinaryOp, rhs: &Value) -> Result<Value, ExprError> {
+    let ordering = match (lhs, rhs) {
+        (Value::Number(a), Value::Number(b)) => a.partial_cmp(b),
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        (Value::Bool(a), Value::Bool(b)) => Some(a.cmp(b)),
+        _ => None,
+    };
+    let result = match (op, ordering) {
+        (BinaryOp::Eq, ordering) => ordering.is_some_and(|o| o.is_eq()),
+        (BinaryOp::Ne, ordering) => !ordering.is_some_and(|o| o.is_eq()),
+        (_, None) => {
+            return Err(ExprError::Type(format!("cannot compare {lhs} with {rhs}")));
+        }
+        (BinaryOp::Lt, Some(o)) => o.is_lt(),
+        (BinaryOp::Le, Some(o)) => o.is_le(),
+        (BinaryOp::Gt, Some(o)) => o.is_gt(),
+        (BinaryOp::Ge, Some(o)) => o.is_ge(),
+        (_, Some(_)) => unreachable!("handled by Expr::eval"),
+    };
+    Ok(Value::Bool(result))
+}
+
+fn arithmetic(lhs: &Value, op: BinaryOp, rhs: &Value) -> Result<Value, ExprError> {
+    match (lhs, op, rhs) {
+        (Value::String(_), BinaryOp::Add, _) | (_, BinaryOp::Add, Value::String(_)) => {
+            let mut text = lhs.to_text();
+            text.push_str(&rhs.to_text());
+            Ok(Value::String(text))
+        }
+        (Value::Number(a), _, Value::Number(b)) => Ok(Value::Number(match op {
+            BinaryOp::Add => a + b,
+            BinaryOp::Sub => a - b,
+            BinaryOp::Mul => a * b,
+            BinaryOp::Div => a / b,
+            _ => a % b,
+        })),
+        _ => Err(ExprError::Type(format!(
+            "cannot apply {op:?} to {lhs} and {rhs}"
+        ))),
+    }
+}
+
+// ==============================================
+// BUILT-IN FUNCTIONS
+// ==============================================
+
+fn call(name: &str, args: &[Value]) -> Result<Value, ExprError> {
+    let text = |i: usize| args.get(i).map(Value::to_text).unwrap_or_default();
+    let number = |i: usize| match args.get(i) {
+        Some(Value::Number(n)) => Ok(*n),
+        Some(other) => Err(ExprError::Type(format!("{name}: {other} is not a number"))),
+        None => Err(ExprError::Type(format!(
+            "{name}: missing argument {}",
+            i + 1
+        ))),
+    };
+
+    let value = match name {
+        "snake_case" => Value::String(snake_case(&text(0))),
+        "lower" => Value::String(text(0).to_lowercase()),
+        "upper" => Value::String(text(0).to_uppercase()),
+        "trim" => Value::String(text(0).trim().to_owned()),
+        "replace" => Value::String(text(0).replace(&text(1), &text(2))),
+        "concat" => Value::String(args.iter().map(Value::to_text).collect()),
+        "str" => Value::String(text(0)),
+        "len" => Value::Number(text(0).chars().count() as f64),
+        "num" => match args.first() {
+            Some(Value::Number(n)) => Value::Number(*n),
+            Some(Value::Bool(b)) => Value::Number(if *b { 1.0 } else { 0.0 }),
+            _ => Value::Number(
+                text(0)
+                    .trim()
+                    .parse()
+                    .map_err(|_err| ExprError::Type(format!("num: cannot parse {:?}", text(0))))?,
+            ),
+        },
+        "abs" => Value::Number(number(0)?.abs()),
+        "round" => Value::Number(number(0)?.round()),
+        "floor" => Value::Number(number(0)?.floor()),
+        "ceil" => Value::Number(number(0)?.ceil()),
+        "min" => Value::Number(number(0)?.min(number(1)?)),
+        "max" => Value::Number(number(0)?.max(number(1)?)),
+        "if" => match args {
+            [condition, then, otherwise] => {
+                if condition.truthy() {
+                    then.clone()
+                } else {
+                    otherwise.clone()
+                }
+            }
+            _ => return Err(ExprError::Type("if: expected 3 arguments".to_owned())),
+        },
+        _ => return Err(ExprError::Parse(format!("unknown function `{name}`"))),
+    };
+    Ok(value)
+}
+
+/// `Jade Gold Decorated 8` and `jadeGoldDecorated8` both become `jade_gold_decorated_8`.
+pub fn snake_case(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut prev_lower = false;
+    for c in text.chars() {
+        if c.is_alphanumeric() {
+            if c.is_uppercase() && prev_lower {
+                out.push('_');
+            }
+            out.extend(c.to_lowercase());
+            prev_lower = c.is_lowercase() || c.is_ascii_digit();
+        } else {
+            if !out.is_empty() && !out.ends_with('_') {
+                out.push('_');
+            }
+            prev_lower = false;
+        }
+    }
+    while out.ends_with('_') {
+        out.pop();
+    }
+    out
+}
+
+// ==============================================
+// PARSING
+// ==============================================
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    String(String),
+    Ident(String),
+    Op(&'static str),
+    LParen,
+    RParen,
+    Comma,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Number(n) => write!(f, "{n}"),
+            Self::String(s) => write!(f, "{s:?}"),
+            Self::Ident(name) => write!(f, "{name}"),
+            Self::Op(op) => write!(f, "{op}"),
+            Self::LParen => write!(f, "("),
+            Self::RParen => write!(f, ")"),
+            Self::Comma => write!(f, ","),
+        }
+    }
+}
+
+const OPERATORS: [&str; 14] = [
+    "&&", "||", "==", "!=", "<=", ">=", "<", ">", "!", "+", "-", "*", "/", "%",
+];
+
+fn tokenize(source: &str) -> Result<Vec<Token>, ExprError> {
+    let mut tokens = Vec::new();
+    let mut rest = source.trim_start();
+
+    while let Some(c) = rest.chars().next() {
+        let len = if let Some(op) = OPERATORS.iter().find(|op| rest.starts_with(**op)) {
+            tokens.push(Token::Op(op));
+            op.len()
+        } else if c == '(' {
+            tokens.push(Token::LParen);
+            1
+        } else if c == ')' {
+            tokens.push(Token::RParen);
+            1
+        } else if c == ',' {
+            tokens.push(Token::Comma);
+            1
+        } else if c == '"' || c == '\'' {
+            let body = rest.split_at(1).1;
+            let end = body
+                .find(c)
+                .ok_or_else(|| ExprError::Parse("unterminated string".to_owned()))?;
+            tokens.push(Token::String(body.split_at(end).0.to_owned()));
+            end + 2
+        } else if c.is_ascii_digit() {
+            let end = rest
+                .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                .unwrap_or(rest.len());
+            let text = rest.split_at(end).0;
+            let number = text
+                .parse()
+                .map_err(|_err| ExprError::Parse(format!("invalid number `{text}`")))?;
+            tokens.push(Token::Number(number));
+            end
+        } else if c.is_alphabetic() || c == '_' {
+            let end = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.'))
+                .unwrap_or(rest.len());
+            tokens.push(Token::Ident(rest.split_at(end).0.to_owned()));
+            end
+        } else {
+            return Err(ExprError::Parse(format!("unexpected character `{c}`")));
+        };
+        rest = rest.split_at(len).1.trim_start();
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat_op(&mut self, ops: &[&'static str]) -> Option<&'static str> {
+        match self.peek() {
+            Some(Token::Op(op)) if ops.contains(op) => {
+                let op = *op;
+                self.pos += 1;
+                Some(op)
+            }
+            _ => None,
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr, ExprError> {
+        let mut expr = self.and()?;
+        while self.eat_op(&["||"]).is_some() {
+            expr = Expr::Binary(Box::new(expr), BinaryOp::Or, Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, ExprError> {
+        let mut expr = self.comparison()?;
+        while self.eat_op(&["&&"]).is_some() {
+            expr = Expr::Binary(Box::new(expr), BinaryOp::And, Box::new(self.comparison()?));
+        }
+        Ok(expr)
+    }
+
+    fn comparison(&mut self) -> Result<Expr, ExprError> {
+        let lhs = self.additive()?;
+        let op = match self.eat_op(&["==", "!=", "<=", ">=", "<", ">"]) {
+            Some("==") => BinaryOp::Eq,
+            Some("!=") => BinaryOp::Ne,
+            Some("<=") => BinaryOp::Le,
+            Some(">=") => BinaryOp::Ge,
+            Some("<") => BinaryOp::Lt,
+            Some(">") => BinaryOp::Gt,
+            _ => return Ok(lhs),
+        };
+        Ok(Expr::Binary(Box::new(lhs), op, Box::new(self.additive()?)))
+    }
+
+    fn additive(&mut self) -> Result<Expr, ExprError> {
+        let mut expr = self.multiplicative()?;
+        while let Some(op) = self.eat_op(&["+", "-"]) {
+            let op = if op == "+" {
+                BinaryOp::Add
+            } else {
+                BinaryOp::Sub
+            };
+            expr = Expr::Binary(Box::new(expr), op, Box::new(self.multiplicative()?));
+        }
+        Ok(expr)
+    }
+
+    fn multiplicative(&mut self) -> Result<Expr, ExprError> {
+        let mut expr = self.unary()?;
+        while let Some(op) = self.eat_op(&["*", "/", "%"]) {
+            let op = match op {
+                "*" => BinaryOp::Mul,
+                "/" => BinaryOp::Div,
+                _ => BinaryOp::Rem,
+            };
+            expr = Expr::Binary(Box::new(expr), op, Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, ExprError> {
+        if self.eat_op(&["!"]).is_some() {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        if self.eat_op(&["-"]).is_some() {
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        self.primary()
+    }
+
+    fn arguments(&mut self) -> Result<Vec<Expr>, ExprError> {
+        let mut args = Vec::new();
+        if self.peek() == Some(&Token::RParen) {
+            self.pos += 1;
+            return Ok(args);
+        }
+        loop {
+            args.push(self.or()?);
+            match self.next() {
+                Some(Token::Comma) => {}
+                Some(Token::RParen) => return Ok(args),
+                _ => return Err(ExprError::Parse("expected `,` or `)`".to_owned())),
+            }
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr, ExprError> {
+        match self.next() {
+            Some(Token::Number(n)) => Ok(Expr::Literal(Value::Number(n))),
+            Some(Token::String(s)) => Ok(Expr::Literal(Value::String(s))),
+            Some(Token::Ident(name)) => Ok(match name.as_str() {
+                "true" => Expr::Literal(Value::Bool(true)),
+                "false" => Expr::Literal(Value::Bool(false)),
+                _ if self.peek() == Some(&Token::LParen) => {
+                    self.pos += 1;
+                    Expr::Call(name, self.arguments()?)
+                }
+                _ => Expr::Field(name),
+            }),
+            Some(Token::LParen) => {
+                let expr = self.or()?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(expr),
+                    _ => Err(ExprError::Parse("expected `)`".to_owned())),
+                }
+            }
+            Some(token) => Err(ExprError::Parse(format!("unexpected `{token}`"))),
+            None => Err(ExprError::Parse("unexpected end of expression".to_owned())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BinaryOp, Expr, ExprError, Value, snake_case};
+
+    fn eval(source: &str) -> Result<Value, ExprError> {
+        let resolve = |name: &str| match name {
+            "enable_furnace" => Some(Value::Bool(true)),
+            "data.hardness" => Some(Value::Number(2.5)),
+            "data.name" => Some(Value::String("Jade Gold".to_owned())),
+            _ => None,
+        };
+        Expr::parse(source)?.eval(&resolve)
+    }
+
+    #[test]
+    fn parses_by_precedence() {
+        let expr = Expr::parse("a || b && c == 1 + 2 * 3").expect("valid expression");
+        let Expr::Binary(lhs, BinaryOp::Or, rhs) = expr else {
+            panic!("`||` should bind loosest, got {expr:?}");
+        };
+        assert_eq!(*lhs, Expr::Field("a".to_owned()), "left of `||`");
+        let Expr::Binary(_, BinaryOp::And, comparison) = *rhs else {
+            panic!("`&&` should bind looser than `==`");
+        };
+        let Expr::Binary(_, BinaryOp::Eq, sum) = *comparison else {
+            panic!("`==` should bind looser than `+`");
+        };
+        assert!(
+            matches!(*sum, Expr::Binary(_, BinaryOp::Add, _)),
+            "`+` should bind looser than `*`"
+        );
+    }
+
+    #[test]
+    fn lists_fields() {
+        let expr = Expr::parse("!enable_furnace || snake_case(data.name) != \"\"")
+            .expect("valid expression");
+        assert_eq!(
+            expr.fields(),
+            ["enable_furnace", "data.name"],
+            "read fields"
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        for source in ["1 +", "(1", "\"open", "1 2", "a $ b", "max(1 2)"] {
+            assert!(
+                matches!(Expr::parse(source), Err(ExprError::Parse(_))),
+                "`{source}` should not parse"
+            );
+        }
+    }
+
+    #[test]
+    fn evaluates_arithmetic_and_comparisons() {
+        assert_eq!(eval("1 + 2 * 3"), Ok(Value::Number(7.0)), "precedence");
+        assert_eq!(eval("(1 + 2) * 3"), Ok(Value::Number(9.0)), "parentheses");
+        assert_eq!(
+            eval("-data.hardness % 2"),
+            Ok(Value::Number(-0.5)),
+            "negation"
+        );
+        assert_eq!(
+            eval("data.hardness > 1"),
+            Ok(Value::Bool(true)),
+            "comparison"
+        );
+        assert_eq!(eval("'a' < 'b'"), Ok(Value::Bool(true)), "string order");
+        assert_eq!(
+            eval("1 == 'a'"),
+            Ok(Value::Bool(false)),
+            "mixed types are unequal"
+        );
+        assert!(
+            matches!(eval("1 < 'a'"), Err(ExprError::Type(_))),
+            "mixed types cannot be ordered"
+        );
+    }
+
+    #[test]
+    fn short_circuits_logic() {
+        assert_eq!(
+            eval("enable_furnace || missing"),
+            Ok(Value::Bool(true)),
+            "`||` skips its right side when the left is truthy"
+        );
+        assert_eq!(
+            eval("!enable_furnace && missing"),
+            Ok(Value::Bool(false)),
+            "`&&` skips its right side when the left is falsy"
+        );
+        assert_eq!(
+            eval("enable_furnace && missing"),
+            Err(ExprError::UnknownField("missing".to_owned())),
+            "unknown fields are reported"
+        );
+    }
+
+    #[test]
+    fn calls_functions() {
+        assert_eq!(
+            eval("snake_case(data.name) + '_' + str(3)"),
+            Ok(Value::String("jade_gold_3".to_owned())),
+            "string concatenation"
+        );
+        assert_eq!(
+            eval("if(data.hardness > 2, 'hard', 'soft')"),
+            Ok(Value::String("hard".to_owned())),
+            "if"
+        );
+        assert_eq!(
+            eval("max(round(data.hardness), 1)"),
+            Ok(Value::Number(3.0)),
+            "max"
+        );
+        assert_eq!(
+            eval("num(' 4 ') + len('abc')"),
+            Ok(Value::Number(7.0)),
+            "num"
+        );
+        assert!(
+            matches!(eval("nope(1)"), Err(ExprError::Parse(_))),
+            "unknown functions are reported"
+        );
+        assert!(
+            matches!(eval("abs('x')"), Err(ExprError::Type(_))),
+            "abs needs a number"
+        );
+    }
+
+    #[test]
+    fn snake_cases_names() {
+        assert_eq!(
+            snake_case("Jade Gold Decorated 8"),
+            "jade_gold_decorated_8",
+            "spaces"
+        );
+        assert_eq!(
+            snake_case("jadeGoldDecorated"),
+            "jade_gold_decorated",
+            "camel case"
+        );
+        assert_eq!(snake_case("  --Jade--  "), "jade", "separators are trimmed");
+    }
+}
@@ -1,23 +1,100 @@
+use std::{fmt, fs, mem, path::Path};
+
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
+use crate::editor::form::{
+    color::{ColorValue, Rgba},
+    options::OptionSet,
+    preserve,
+    schema::{SchemaError, overrides},
+};
+
+#[derive(Debug)]
+pub enum DocumentError {
+    IoError(std::io::Error),
+    ParseError(toml::de::Error),
+    SerializeError(toml::ser::Error),
+    SchemaError(SchemaError),
+}
+
+impl fmt::Display for DocumentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IoError(err) => write!(f, "{err}"),
+            Self::ParseError(err) => write!(f, "{err}"),
+            Self::SerializeError(err) => write!(f, "{err}"),
+            Self::SchemaError(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl From<std::io::Error> for DocumentError {
+    fn from(err: std::io::Error) -> Self {
+        Self::IoError(err)
+    }
+}
+
+impl From<toml::de::Error> for DocumentError {
+    fn from(err: toml::de::Error) -> Self {
+        Self::ParseError(err)
+    }
+}
+
+impl From<SchemaError> for DocumentError {
+    fn from(err: SchemaError) -> Self {
+        Self::SchemaError(err)
+    }
+}
+
+impl From<toml::ser::Error> for DocumentError {
+    fn from(err: toml::ser::Error) -> Self {
+        Self::SerializeError(err)
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Field {
     pub label: Option<String>,
+    /// Short help, shown when hovering the field.
     pub description: Option<String>,
+    /// Long help in Markdown, shown in the help panel while the field is focused.
+    pub help: Option<String>,
+    /// Link to further documentation of the field.
+    pub docs: Option<String>,
+    /// Example values, listed in the help panel.
+    pub examples: Option<Vec<toml::Value>>,
     pub editable: Option<bool>,
     pub template: Option<bool>,
-    pub convert: Option<FieldType>,
+    /// Types the field may be converted to from its context menu.
+    pub convert: Option<ConvertTargets>,
+    /// Expression deciding whether the field is shown, e.g. `enable_furnace`.
+    pub visible_if: Option<String>,
+    /// Expression deciding whether the field can be edited.
+    pub enabled_if: Option<String>,
+    /// Expression the value is computed from, e.g. `snake_case(data.name)`.
+    pub compute: Option<String>,
+    /// Offer the computed value as a suggestion instead of making the field read-only.
+    pub suggest: Option<bool>,
+    /// Set once the user detaches the field from its `compute` formula.
+    pub detached: Option<bool>,
+    /// Name of a registered custom widget to edit the field with, e.g. `multiline`.
+    pub widget: Option<String>,
+    /// The field may be left unset, so the game uses its own default.
+    pub optional: Option<bool>,
+    /// Set while an optional field is unset. Its value is kept, but not exported.
+    pub unset: Option<bool>,
     #[serde(flatten)]
     pub value: FieldValue,
 }
 #[derive(Serialize, Deserialize, Clone)]
 pub struct EnumField {
     pub value: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub options: Vec<String>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum FieldType {
     String,
@@ -27,6 +104,28 @@ pub enum FieldType {
     Image,
     Table,
     Array,
+    Enum,
+    Color,
+    Vec2,
+    Vec3,
+    Aabb,
+}
+
+/// The `convert` key of a field: a single type or a list of types.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ConvertTargets {
+    One(FieldType),
+    Many(Vec<FieldType>),
+}
+
+impl ConvertTargets {
+    pub fn types(&self) -> &[FieldType] {
+        match self {
+            Self::One(field_type) => std::slice::from_ref(field_type),
+            Self::Many(types) => types,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -36,13 +135,15 @@ pub enum FieldValue {
     String {
         value: String,
         default: Option<String>,
+        /// Edit in a text area with a preview of `§` formatting codes.
+        multiline: Option<bool>,
+        /// Most characters shown in game, see
+        /// [`visible_len`](crate::editor::form::formatting::visible_len).
+        max_length: Option<usize>,
+        max_lines: Option<usize>,
     },
     #[serde(rename = "image")]
-    Image {
-        value: String,
-        #[serde(skip)]
-        texture: Option<egui::TextureHandle>,
-    },
+    Image { value: String },
     #[serde(rename = "float")]
     Float { value: f64, default: Option<f64> },
     #[serde(rename = "integer")]
@@ -65,6 +166,41 @@ pub enum FieldValue {
         value: String,
         #[serde(default)]
         options: Vec<String>,
+        /// Named option set listed before `options`, see [`OptionSet`].
+        options_from: Option<String>,
+        /// Accept values that are not among the options.
+        free_text: Option<bool>,
+    },
+    /// A color stored as a hex string or an integer, see [`ColorValue`].
+    #[serde(rename = "color")]
+    Color {
+        value: ColorValue,
+        /// Whether the color has an alpha channel.
+        alpha: Option<bool>,
+        default: Option<ColorValue>,
+    },
+    /// Two floats, e.g. an offset on a face. `min` / `max` bound every component.
+    #[serde(rename = "vec2")]
+    Vec2 {
+        value: [f64; 2],
+        default: Option<[f64; 2]>,
+        min: Option<f64>,
+        max: Option<f64>,
+    },
+    #[serde(rename = "vec3")]
+    Vec3 {
+        value: [f64; 3],
+        default: Option<[f64; 3]>,
+        min: Option<f64>,
+        max: Option<f64>,
+    },
+    /// A box as `[minX, minY, minZ, maxX, maxY, maxZ]`, e.g. the bounds of a block.
+    #[serde(rename = "aabb")]
+    Aabb {
+        value: [f64; 6],
+        default: Option<[f64; 6]>,
+        min: Option<f64>,
+        max: Option<f64>,
     },
 }
 
@@ -72,7 +208,12 @@ pub enum FieldValue {
 #[serde(tag = "array_type")]
 pub enum ArrayValue {
     #[serde(rename = "enums")]
-    Enums { items: Vec<EnumField> },
+    Enums {
+        /// Option set shared by every item, listed before the item's own options.
+        options_from: Option<String>,
+        free_text: Option<bool>,
+        items: Vec<EnumField>,
+    },
 
     #[serde(rename = "strings")]
     Strings { items: Vec<String> },
@@ -84,25 +225,446 @@ pub enum ArrayValue {
     Integers { items: Vec<i64> },
 
     #[serde(rename = "objects")]
-    Objects { items: Vec<IndexMap<String, Field>> },
+    Objects {
+        /// Fields every new item starts with.
+        item: Option<IndexMap<String, Field>>,
+        min_items: Option<usize>,
+        max_items: Option<usize>,
+        /// Child field whose value labels each item's header, e.g. `name`.
+        item_label: Option<String>,
+        items: Vec<IndexMap<String, Field>>,
+    },
 
     #[serde(rename = "mixed")]
     Mixed { items: Vec<toml::Value> },
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct Document {
+    /// Name of the schema the document was created from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<String>,
+    /// Option sets enum fields can refer to with `options_from`.
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub option_sets: IndexMap<String, OptionSet>,
     #[serde(flatten)]
     pub fields: IndexMap<String, Field>,
+    /// Leave fields holding their default out of saved files. Set from the project
+    /// manifest when the document is loaded.
+    #[serde(skip)]
+    pub omit_defaults_on_save: bool,
+    /// What the document inherits through its `extends` and `include` keys. Set when
+    /// the document is loaded.
+    #[serde(skip)]
+    pub inherited: Option<Inherited>,
+}
+
+/// The schema and fragments a document extends and includes, so saving writes only what
+/// the document itself sets.
+#[derive(Clone)]
+pub struct Inherited {
+    /// The document's own `extends` and `include` keys.
+    pub keys: toml::Table,
+    /// The table those keys resolve to.
+    pub base: toml::Table,
+    /// The rest of the document's own file. Its keys are kept even where they repeat
+    /// the base.
+    pub own: toml::Table,
 }
 
 impl Document {
     pub fn new(fields: IndexMap<String, Field>) -> Self {
-        Self { fields }
+        Self {
+            schema: None,
+            option_sets: IndexMap::new(),
+            fields,
+            omit_defaults_on_save: false,
+            inherited: None,
+        }
     }
     pub fn from_toml(toml_str: &str) -> Result<Self, toml::de::Error> {
-        let doc: Document = toml::from_str(toml_str)?;
-        Ok(doc)
+        Self::from_table(toml::from_str(toml_str)?)
+    }
+
+    /// Builds a document from parsed TOML, accepting the short `value = [...]` form of
+    /// arrays.
+    ///
+    /// # Errors
+    /// Fails if the table is not a valid document.
+    pub fn from_table(mut table: toml::Table) -> Result<Self, toml::de::Error> {
+        normalize_arrays(&mut table);
+        toml::Value::Table(table).try_into()
+    }
+
+    /// Reads and parses a document file.
+    ///
+    /// # Errors
+    /// Fails if the file cannot be read or is not a valid document.
+    pub fn from_file(path: &Path) -> Result<Self, DocumentError> {
+        let content = fs::read_to_string(path)?;
+        Ok(Self::from_toml(&content)?)
+    }
+
+    /// Serializes the document, schema data included.
+    ///
+    /// # Errors
+    /// Fails if a value cannot be represented in TOML.
+    pub fn to_toml(&self) -> Result<String, toml::ser::Error> {
+        toml::to_string(self)
+    }
+
+    /// Writes the document to `path`. An existing file is updated in place, keeping its
+    /// comments and the formatting of unchanged values.
+    ///
+    /// Unset optional fields are left out, and so are fields holding their default with
+    /// [`Document::omit_defaults_on_save`]; see [`Document::restore_omitted`]. A document
+    /// that extends a schema or includes fragments keeps those keys and writes only the
+    /// values it overrides.
+    ///
+    /// # Errors
+    /// Fails if the document cannot be serialized or the file cannot be written.
+    pub fn save(&self, path: &Path) -> Result<(), DocumentError> {
+        let text = match fs::read_to_string(path) {
+            Ok(original) => self.to_toml_preserving(&original)?,
+            Err(_) => toml::to_string(&self.saved_table()?)?,
+        };
+        fs::write(path, text)?;
+        Ok(())
+    }
+
+    /// Serializes the document as [`Document::save`] writes it, as an edit of `original`,
+    /// see [`preserve::update`]. Text that is not valid TOML is replaced entirely.
+    ///
+    /// # Errors
+    /// Fails if a value cannot be represented in TOML.
+    pub fn to_toml_preserving(&self, original: &str) -> Result<String, toml::ser::Error> {
+        let table = self.saved_table()?;
+        match preserve::update(original, &table) {
+            Ok(text) => Ok(text),
+            Err(_) => toml::to_string(&table),
+        }
+    }
+
+    /// The table [`Document::save`] writes.
+    fn saved_table(&self) -> Result<toml::Table, toml::ser::Error> {
+        let Some(inherited) = &self.inherited else {
+            return toml::Table::try_from(self.saved());
+        };
+        // Unset fields are written as such, or they would inherit the base's value.
+        let mut document = self.clone();
+        document.spell_out_unset();
+        let table = toml::Table::try_from(document)?;
+        let mut saved = inherited.keys.clone();
+        saved.extend(overrides(table, &inherited.base, &inherited.own));
+        Ok(saved)
+    }
+
+    /// The document as it is saved: without unset optional fields, so the game uses its
+    /// default, nor, if so configured, fields holding their default.
+    fn saved(&self) -> Self {
+        let mut saved = self.clone();
+        Self::retain_saved(&mut saved.fields, self.omit_defaults_on_save);
+        saved
+    }
+
+    fn retain_saved(fields: &mut IndexMap<String, Field>, omit_defaults: bool) {
+        fields.retain(|_, field| {
+            let omitted =
+                field.is_unset() || (omit_defaults && field.value.is_default() == Some(true));
+            !omitted
+        });
+        for field in fields.values_mut() {
+            if let FieldValue::Table { children } = &mut field.value {
+                Self::retain_saved(children, omit_defaults);
+            }
+        }
+    }
+
+    /// Writes `unset = false` on every set optional field, so it overrides an inherited
+    /// `unset = true`.
+    pub fn spell_out_unset(&mut self) {
+        Self::spell_out_fields(&mut self.fields);
+    }
+
+    fn spell_out_fields(fields: &mut IndexMap<String, Field>) {
+        for field in fields.values_mut() {
+            if field.is_optional() && !field.is_unset() {
+                field.unset = Some(false);
+            }
+            if let FieldValue::Table { children } = &mut field.value {
+                Self::spell_out_fields(children);
+            }
+        }
+    }
+
+    /// Adds back the fields [`Document::save`] left out, from the schema the document was
+    /// created from: optional fields missing from the document come back unset and, with
+    /// [`Document::omit_defaults_on_save`], fields with a default come back holding it.
+    pub fn restore_omitted(&mut self, schema: &Self) {
+        Self::restore_fields(&mut self.fields, &schema.fields, self.omit_defaults_on_save);
+    }
+
+    fn restore_fields(
+        fields: &mut IndexMap<String, Field>,
+        schema: &IndexMap<String, Field>,
+        omit_defaults: bool,
+    ) {
+        for (index, (key, schema_field)) in schema.iter().enumerate() {
+            match (fields.get_mut(key), &schema_field.value) {
+                (
+                    Some(Field {
+                        value: FieldValue::Table { children },
+                        ..
+                    }),
+                    FieldValue::Table {
+                        children: schema_children,
+                    },
+                ) => Self::restore_fields(children, schema_children, omit_defaults),
+                (None, value)
+                    if schema_field.is_optional()
+                        || (omit_defaults && value.is_default().is_some()) =>
+                {
+                    let mut field = schema_field.clone();
+                    if field.is_optional() {
+                        field.set_unset(true);
+                    } else {
+                        field.value.reset();
+                    }
+                    // Back where the schema has it, as far as the document allows.
+                    fields.shift_insert(index.min(fields.len()), key.clone(), field);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Looks up a field by its dotted path, e.g. `sounds.sound`.
+    pub fn get(&self, path: &str) -> Option<&Field> {
+        let mut parts = path.split('.');
+        let mut field = self.fields.get(parts.next()?)?;
+        for part in parts {
+            match &field.value {
+                FieldValue::Table { children } => field = children.get(part)?,
+                _ => return None,
+            }
+        }
+        Some(field)
+    }
+
+    pub fn get_mut(&mut self, path: &str) -> Option<&mut Field> {
+        let mut parts = path.split('.');
+        let mut field = self.fields.get_mut(parts.next()?)?;
+        for part in parts {
+            match &mut field.value {
+                FieldValue::Table { children } => field = children.get_mut(part)?,
+                _ => return None,
+            }
+        }
+        Some(field)
+    }
+
+    /// Dotted paths of every non-table field, in document order.
+    pub fn leaf_paths(&self) -> Vec<String> {
+        let mut paths = Vec::new();
+        Self::collect_leaf_paths(&self.fields, "", &mut paths);
+        paths
+    }
+
+    fn collect_leaf_paths(fields: &IndexMap<String, Field>, prefix: &str, paths: &mut Vec<String>) {
+        for (key, field) in fields {
+            let path = join_path(prefix, key);
+            match &field.value {
+                FieldValue::Table { children } => Self::collect_leaf_paths(children, &path, paths),
+                _ => paths.push(path),
+            }
+        }
+    }
+
+    /// Paths of every table field, in document order. Tables inside the items of object
+    /// arrays are included with the item index, e.g. `drops[0].conditions`.
+    pub fn table_paths(&self) -> Vec<String> {
+        let mut paths = Vec::new();
+        Self::collect_table_paths(&self.fields, "", &mut paths);
+        paths
+    }
+
+    fn collect_table_paths(
+        fields: &IndexMap<String, Field>,
+        prefix: &str,
+        paths: &mut Vec<String>,
+    ) {
+        for (key, field) in fields {
+            let path = join_path(prefix, key);
+            match &field.value {
+                FieldValue::Table { children } => {
+                    paths.push(path.clone());
+                    Self::collect_table_paths(children, &path, paths);
+                }
+                FieldValue::Array {
+                    value: ArrayValue::Objects { items, .. },
+                } => {
+                    for (i, item) in items.iter().enumerate() {
+                        Self::collect_table_paths(item, &format!("{path}[{i}]"), paths);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Rewrites array fields written as `value = [...]` to the `array_type` / `items` form.
+/// Arrays of one scalar type get that type; anything else, nested arrays included, is
+/// `mixed`.
+fn normalize_arrays(table: &mut toml::Table) {
+    let is_short_array = table.get("type").and_then(toml::Value::as_str) == Some("array")
+        && !table.contains_key("array_type");
+    if let (true, Some(toml::Value::Array(items))) = (is_short_array, table.get("value")) {
+        let items = items.clone();
+        let array_type = match items.first().map(toml::Value::type_str) {
+            Some(first) if items.iter().any(|item| item.type_str() != first) => "mixed",
+            None | Some("string") => "strings",
+            Some("integer") => "integers",
+            Some("float") => "floats",
+            Some(_) => "mixed",
+        };
+        table.remove("value");
+        table.insert("array_type".to_owned(), array_type.into());
+        table.insert("items".to_owned(), toml::Value::Array(items));
+    }
+
+    for (_, value) in table.iter_mut() {
+        if let toml::Value::Table(child) = value {
+            normalize_arrays(child);
+        }
+    }
+}
+
+/// Appends `key` to a dotted field path.
+pub fn join_path(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_owned()
+    } else {
+        format!("{prefix}.{key}")
+    }
+}
+
+impl FieldValue {
+    pub fn field_type(&self) -> FieldType {
+        match self {
+            Self::String { .. } => FieldType::String,
+            Self::Image { .. } => FieldType::Image,
+            Self::Float { .. } => FieldType::Float,
+            Self::Integer { .. } => FieldType::Integer,
+            Self::Boolean { .. } => FieldType::Boolean,
+            Self::Table { .. } => FieldType::Table,
+            Self::Array { .. } => FieldType::Array,
+            Self::Enum { .. } => FieldType::Enum,
+            Self::Color { .. } => FieldType::Color,
+            Self::Vec2 { .. } => FieldType::Vec2,
+            Self::Vec3 { .. } => FieldType::Vec3,
+            Self::Aabb { .. } => FieldType::Aabb,
+        }
+    }
+
+    /// Whether both values are of the same field type, e.g. both enums with the same options.
+    pub fn same_kind(&self, other: &Self) -> bool {
+        match (self, other) {
+            (
+                Self::Enum {
+                    options: a,
+                    options_from: from_a,
+                    ..
+                },
+                Self::Enum {
+                    options: b,
+                    options_from: from_b,
+                    ..
+                },
+            ) => a == b && from_a == from_b,
+            _ => mem::discriminant(self) == mem::discriminant(other),
+        }
+    }
+
+    /// Compares the stored values, ignoring defaults and other schema data.
+    pub fn value_eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::String { value: a, .. }, Self::String { value: b, .. })
+            | (Self::Image { value: a, .. }, Self::Image { value: b, .. })
+            | (Self::Enum { value: a, .. }, Self::Enum { value: b, .. }) => a == b,
+            (Self::Float { value: a, .. }, Self::Float { value: b, .. }) => a == b,
+            (Self::Integer { value: a, .. }, Self::Integer { value: b, .. }) => a == b,
+            (Self::Boolean { value: a, .. }, Self::Boolean { value: b, .. }) => a == b,
+            (Self::Color { value: a, .. }, Self::Color { value: b, .. }) => a == b,
+            (Self::Vec2 { value: a, .. }, Self::Vec2 { value: b, .. }) => a == b,
+            (Self::Vec3 { value: a, .. }, Self::Vec3 { value: b, .. }) => a == b,
+            (Self::Aabb { value: a, .. }, Self::Aabb { value: b, .. }) => a == b,
+            _ => false,
+        }
+    }
+
+    /// Whether the value equals the schema's `default`, or `None` if there is none.
+    pub fn is_default(&self) -> Option<bool> {
+        match self {
+            Self::String { value, default, .. } => default.as_ref().map(|d| d == value),
+            Self::Float { value, default } => default.map(|d| d == *value),
+            Self::Integer { value, default } => default.map(|d| d == *value),
+            Self::Boolean { value, default } => default.map(|d| d == *value),
+            Self::Color { value, default, .. } => default.as_ref().map(|d| d == value),
+            Self::Vec2 { value, default, .. } => default.map(|d| d == *value),
+            Self::Vec3 { value, default, .. } => default.map(|d| d == *value),
+            Self::Aabb { value, default, .. } => default.map(|d| d == *value),
+            _ => None,
+        }
+    }
+
+    /// Sets the value back to the schema's `default`. Returns whether it changed.
+    pub fn reset(&mut self) -> bool {
+        fn reset_to<T: PartialEq + Clone>(value: &mut T, default: Option<&T>) -> bool {
+            let Some(default) = default else {
+                return false;
+            };
+            let changed = value != default;
+            value.clone_from(default);
+            changed
+        }
+
+        match self {
+            Self::String { value, default, .. } => reset_to(value, default.as_ref()),
+            Self::Float { value, default } => reset_to(value, default.as_ref()),
+            Self::Integer { value, default } => reset_to(value, default.as_ref()),
+            Self::Boolean { value, default } => reset_to(value, default.as_ref()),
+            Self::Color { value, default, .. } => reset_to(value, default.as_ref()),
+            Self::Vec2 { value, default, .. } => reset_to(value, default.as_ref()),
+            Self::Vec3 { value, default, .. } => reset_to(value, default.as_ref()),
+            Self::Aabb { value, default, .. } => reset_to(value, default.as_ref()),
+            _ => false,
+        }
+    }
+
+    /// Copies the stored value of `other` into `self`, keeping the schema data of `self`.
+    pub fn set_value_from(&mut self, other: &Self) {
+        match (self, other) {
+            (Self::String { value, .. }, Self::String { value: new, .. })
+            | (Self::Image { value, .. }, Self::Image { value: new, .. })
+            | (Self::Enum { value, .. }, Self::Enum { value: new, .. }) => {
+                value.clone_from(new);
+            }
+            (Self::Float { value, .. }, Self::Float { value: new, .. }) => *value = *new,
+            (Self::Integer { value, .. }, Self::Integer { value: new, .. }) => {
+                *value = *new;
+            }
+            (Self::Boolean { value, .. }, Self::Boolean { value: new, .. }) => {
+                *value = *new;
+            }
+            (Self::Color { value, .. }, Self::Color { value: new, .. }) => {
+                value.clone_from(new);
+            }
+            (Self::Vec2 { value, .. }, Self::Vec2 { value: new, .. }) => *value = *new,
+            (Self::Vec3 { value, .. }, Self::Vec3 { value: new, .. }) => *value = *new,
+            (Self::Aabb { value, .. }, Self::Aabb { value: new, .. }) => *value = *new,
+            _ => {}
+        }
     }
 }
 
@@ -113,113 +675,250 @@ impl Default for ArrayValue {
 }
 
 impl Field {
-    pub fn default_string() -> Self {
-        Self {
-            label: None,
-            description: None,
-            editable: Some(true),
-            template: Some(false),
-            value: FieldValue::String {
-                value: String::new(),
-                default: None,
-            },
-            convert: None,
+    pub fn is_optional(&self) -> bool {
+        self.optional.unwrap_or(false)
+    }
+
+    /// Whether the field is optional and currently unset. `unset` is ignored on fields
+    /// that are not optional.
+    pub fn is_unset(&self) -> bool {
+        self.is_optional() && self.unset.unwrap_or(false)
+    }
+
+    pub fn set_unset(&mut self, unset: bool) {
+        self.unset = unset.then_some(true);
+    }
+
+    /// Whether the field, or any field of a table, holds something other than its
+    /// default. Fields without a default never differ.
+    pub fn differs_from_default(&self) -> bool {
+        match &self.value {
+            FieldValue::Table { children } => children.values().any(Self::differs_from_default),
+            value => value.is_default() == Some(false),
         }
     }
 
-    pub fn default_float() -> Self {
-        Self {
-            label: None,
-            description: None,
-            editable: Some(true),
-            value: FieldValue::Float {
-                value: 0.0,
-                default: None,
-            },
-            template: Some(false),
-            convert: None,
+    /// Resets the field, or every field of a table, to its default. Returns whether
+    /// anything changed.
+    pub fn reset(&mut self) -> bool {
+        match &mut self.value {
+            FieldValue::Table { children } => children
+                .values_mut()
+                .fold(false, |changed, child| child.reset() | changed),
+            value => value.reset(),
         }
     }
 
-    pub fn default_integer() -> Self {
+    /// Replaces the value with `value`, e.g. one of the field's examples.
+    ///
+    /// # Errors
+    /// Fails if `value` is not a valid value for the field's type.
+    pub fn set_value_toml(&mut self, value: toml::Value) -> Result<(), String> {
+        let mut table = toml::Table::try_from(&*self).map_err(|err| err.to_string())?;
+        // Arrays keep their elements in `items`.
+        let key = if table.contains_key("items") {
+            "items"
+        } else {
+            "value"
+        };
+        table.insert(key.to_owned(), value);
+        let field: Self = table.try_into().map_err(|err| err.to_string())?;
+        self.value = field.value;
+        Ok(())
+    }
+
+    /// The schema's `default` of the field as TOML, e.g. `0.6`, if it declares one.
+    pub fn default_text(&self) -> Option<String> {
+        let value = toml::Value::try_from(&self.value).ok()?;
+        value.get("default").map(ToString::to_string)
+    }
+
+    /// A field with no label or description holding `value`.
+    pub fn with_value(value: FieldValue) -> Self {
         Self {
             label: None,
             description: None,
+            help: None,
+            docs: None,
+            examples: None,
             editable: Some(true),
-            value: FieldValue::Integer {
-                value: 0,
-                default: None,
-            },
             template: Some(false),
             convert: None,
+            visible_if: None,
+            enabled_if: None,
+            compute: None,
+            suggest: None,
+            detached: None,
+            widget: None,
+            optional: None,
+            unset: None,
+            value,
         }
     }
 
+    pub fn default_string() -> Self {
+        Self::with_value(FieldValue::String {
+            value: String::new(),
+            default: None,
+            multiline: None,
+            max_length: None,
+            max_lines: None,
+        })
+    }
+
+    pub fn default_float() -> Self {
+        Self::with_value(FieldValue::Float {
+            value: 0.0,
+            default: None,
+        })
+    }
+
+    pub fn default_integer() -> Self {
+        Self::with_value(FieldValue::Integer {
+            value: 0,
+            default: None,
+        })
+    }
+
     pub fn default_boolean() -> Self {
-        Self {
-            label: None,
-            description: None,
-            editable: Some(true),
-            value: FieldValue::Boolean {
-                value: false,
-                default: None,
-            },
-            template: Some(false),
-            convert: None,
-        }
+        Self::with_value(FieldValue::Boolean {
+            value: false,
+            default: None,
+        })
     }
 
     pub fn default_image() -> Self {
-        Self {
-            label: None,
-            description: None,
-            editable: Some(true),
-            value: FieldValue::Image {
-                value: String::new(),
-                texture: None,
-            },
-            template: Some(false),
-            convert: None,
-        }
+        Self::with_value(FieldValue::Image {
+            value: String::new(),
+        })
     }
 
     pub fn default_table() -> Self {
-        Self {
-            label: None,
-            description: None,
-            editable: Some(true),
-            value: FieldValue::Table {
-                children: IndexMap::new(),
-            },
-            template: Some(false),
-            convert: None,
-        }
+        Self::with_value(FieldValue::Table {
+            children: IndexMap::new(),
+        })
     }
 
     pub fn default_array() -> Self {
-        Self {
-            label: None,
-            description: None,
-            editable: Some(true),
-            value: FieldValue::Array {
-                value: ArrayValue::default(),
-            },
-            template: Some(false),
-            convert: None,
-        }
+        Self::with_value(FieldValue::Array {
+            value: ArrayValue::default(),
+        })
     }
 
     pub fn default_enum() -> Self {
-        Self {
-            label: None,
-            description: None,
-            editable: Some(true),
-            value: FieldValue::Enum {
-                value: String::new(),
-                options: Vec::new(),
-            },
-            template: Some(false),
-            convert: None,
+        Self::with_value(FieldValue::Enum {
+            value: String::new(),
+            options: Vec::new(),
+            options_from: None,
+            free_text: None,
+        })
+    }
+
+    pub fn default_color() -> Self {
+        Self::with_value(FieldValue::Color {
+            value: ColorValue::Hex(Rgba::BLACK.to_hex(false)),
+            alpha: None,
+            default: None,
+        })
+    }
+
+    pub fn default_vec2() -> Self {
+        Self::with_value(FieldValue::Vec2 {
+            value: [0.0; 2],
+            default: None,
+            min: None,
+            max: None,
+        })
+    }
+
+    pub fn default_vec3() -> Self {
+        Self::with_value(FieldValue::Vec3 {
+            value: [0.0; 3],
+            default: None,
+            min: None,
+            max: None,
+        })
+    }
+
+    /// A full block, `[0, 0, 0, 1, 1, 1]`.
+    pub fn default_aabb() -> Self {
+        Self::with_value(FieldValue::Aabb {
+            value: [0.0, 0.0, 0.0, 1.0, 1.0, 1.0],
+            default: None,
+            min: None,
+            max: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Document, FieldValue};
+
+    const SOUNDS: &str = r#"
+[sounds]
+type = "table"
+
+[sounds.volume]
+type = "float"
+value = 0.5
+default = 1.0
+
+[sounds.pitch]
+type = "float"
+value = 1.0
+default = 1.0
+
+[sounds.step]
+type = "string"
+value = "gravel"
+
+[sounds.break]
+type = "table"
+
+[sounds.break.delay]
+type = "integer"
+value = 4
+default = 0
+"#;
+
+    fn float(document: &Document, path: &str) -> Option<f64> {
+        match document.get(path).map(|field| &field.value) {
+            Some(FieldValue::Float { value, .. }) => Some(*value),
+            _ => None,
         }
     }
+
+    #[test]
+    fn resets_every_field_of_a_section() {
+        let mut document = Document::from_toml(SOUNDS).expect("valid document");
+        let section = document.get_mut("sounds").expect("sounds section");
+        assert!(section.differs_from_default(), "volume and delay differ");
+        assert!(section.reset(), "something changed");
+        assert!(
+            !section.differs_from_default(),
+            "nothing differs after the reset"
+        );
+        assert!(!section.reset(), "a second reset changes nothing");
+
+        assert_eq!(
+            float(&document, "sounds.volume"),
+            Some(1.0),
+            "back to its default"
+        );
+        assert!(
+            matches!(
+                document.get("sounds.break.delay").map(|f| &f.value),
+                Some(FieldValue::Integer { value: 0, .. })
+            ),
+            "nested sections are reset too"
+        );
+        assert!(
+            matches!(
+                document.get("sounds.step").map(|f| &f.value),
+                Some(FieldValue::String { value, .. }) if value == "gravel"
+            ),
+            "fields without a default keep their value"
+        );
+    }
 }
@@ -0,0 +1,156 @@
+use crate::editor::form::color::Rgba;
+
+/// Starts a formatting code in game text, e.g. `§a` for green or `§l` for bold.
+pub const SECTION: char = '§';
+
+/// A formatting code, the character after [`SECTION`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Code {
+    /// `§0`-`§f`. Also resets the other formatting, as the game does.
+    Color(Rgba),
+    /// `§k`
+    Obfuscated,
+    /// `§l`
+    Bold,
+    /// `§m`
+    Strikethrough,
+    /// `§n`
+    Underline,
+    /// `§o`
+    Italic,
+    /// `§r`
+    Reset,
+}
+
+const PALETTE: [u32; 16] = [
+    0x000000, 0x0000AA, 0x00AA00, 0x00AAAA, 0xAA0000, 0xAA00AA, 0xFFAA00, 0xAAAAAA, 0x555555,
+    0x5555FF, 0x55FF55, 0x55FFFF, 0xFF5555, 0xFF55FF, 0xFFFF55, 0xFFFFFF,
+];
+
+impl Code {
+    pub fn from_char(c: char) -> Option<Self> {
+        let code = match c.to_ascii_lowercase() {
+            'k' => Self::Obfuscated,
+            'l' => Self::Bold,
+            'm' => Self::Strikethrough,
+            'n' => Self::Underline,
+            'o' => Self::Italic,
+            'r' => Self::Reset,
+            c => {
+                let [_, r, g, b] = PALETTE.get(c.to_digit(16)? as usize)?.to_be_bytes();
+                Self::Color(Rgba { r, g, b, a: 255 })
+            }
+        };
+        Some(code)
+    }
+}
+
+/// Formatting in effect for a piece of text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Style {
+    /// `None` is the default text color of wherever the text is shown.
+    pub color: Option<Rgba>,
+    pub obfuscated: bool,
+    pub bold: bool,
+    pub strikethrough: bool,
+    pub underline: bool,
+    pub italic: bool,
+}
+
+impl Style {
+    fn apply(&mut self, code: Code) {
+        match code {
+            Code::Color(color) => {
+                *self = Self {
+                    color: Some(color),
+                    ..Self::default()
+                };
+            }
+            Code::Obfuscated => self.obfuscated = true,
+            Code::Bold => self.bold = true,
+            Code::Strikethrough => self.strikethrough = true,
+            Code::Underline => self.underline = true,
+            Code::Italic => self.italic = true,
+            Code::Reset => *self = Self::default(),
+        }
+    }
+}
+
+/// A piece of formatted text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment<'a> {
+    /// A `§` and the character after it. `code` is `None` for unknown codes and a `§`
+    /// at the end of the text.
+    Code {
+        text: &'a str,
+        code: Option<Code>,
+    },
+    Text {
+        text: &'a str,
+        style: Style,
+    },
+}
+
+/// Splits `text` into its formatting codes and the styled text between them.
+pub fn segments(text: &str) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    let mut style = Style::default();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let Some(start) = rest.find(SECTION) else {
+            segments.push(Segment::Text { text: rest, style });
+            break;
+        };
+        let (before, code_text) = rest.split_at(start);
+        if !before.is_empty() {
+            segments.push(Segment::Text {
+                text: before,
+                style,
+            });
+        }
+        let mut chars = code_text.char_indices().skip(1);
+        let (end, code) = match chars.next() {
+            // `\n` is never part of a code, so lines stay intact.
+            Some((at, c)) if c != '\n' => (at + c.len_utf8(), Code::from_char(c)),
+            _ => (SECTION.len_utf8(), None),
+        };
+        if let Some(code) = code {
+            style.apply(code);
+        }
+        let (code_text, after) = code_text.split_at(end);
+        segments.push(Segment::Code {
+            text: code_text,
+            code,
+        });
+        rest = after;
+    }
+    segments
+}
+
+/// Number of characters shown in game, leaving out formatting codes and line breaks.
+pub fn visible_len(text: &str) -> usize {
+    segments(text)
+        .iter()
+        .map(|segment| match segment {
+            Segment::Text { text, .. } => text.chars().filter(|c| *c != '\n').count(),
+            Segment::Code { .. } => 0,
+        })
+        .sum()
+}
+
+/// Why `text` breaks the `max_length` / `max_lines` limits of its field, if it does.
+pub fn limit_error(
+    text: &str,
+    max_length: Option<usize>,
+    max_lines: Option<usize>,
+) -> Option<String> {
+    let length = visible_len(text);
+    let lines = text.lines().count().max(1);
+    if let Some(max) = max_length.filter(|max| length > *max) {
+        return Some(format!("{length} characters, at most {max} allowed"));
+    }
+    if let Some(max) = max_lines.filter(|max| lines > *max) {
+        return Some(format!("{lines} lines, at most {max} allowed"));
+    }
+    None
+}
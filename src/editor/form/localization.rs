@@ -0,0 +1,357 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use crate::editor::{
+    form::{
+        form::{Document, Field, FieldValue, join_path},
+        schema::SchemaRegistry,
+    },
+    history::{Change, FileChange},
+};
+
+/// Field holding the translation key of a document, e.g. `jade_gold`.
+pub const KEY_FIELD: &str = "lang.key";
+/// Table holding one child table of translated strings per locale.
+pub const LOCALES_FIELD: &str = "lang.locales";
+
+/// A project document with a `lang.locales` table.
+struct LocalizedDocument {
+    path: PathBuf,
+    /// The document as last read from or written to disk.
+    saved: Document,
+    document: Document,
+    dirty: bool,
+}
+
+/// One translatable string of a document, a row of the grid.
+pub struct Entry {
+    document: usize,
+    /// Name of the string inside each locale table, e.g. `name`.
+    pub name: String,
+    /// Full translation key, e.g. `jade_gold.name`.
+    pub key: String,
+    pub label: Option<String>,
+}
+
+/// State of one entry in one locale.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Cell<'a> {
+    /// The document has no such locale or the locale lacks the entry.
+    Missing,
+    /// The entry exists but is blank.
+    Empty,
+    Text(&'a str),
+}
+
+/// Translations of every document of a project, as a grid of entries × locales.
+#[derive(Default)]
+pub struct Localization {
+    documents: Vec<LocalizedDocument>,
+    entries: Vec<Entry>,
+    locales: Vec<String>,
+    /// For each `(entry, locale)` with a near-identical text elsewhere, the other entry.
+    duplicates: HashMap<(usize, usize), usize>,
+    pub errors: Vec<String>,
+}
+
+impl Localization {
+    /// Reads the translations of every document at `paths`. Documents without a
+    /// `lang.locales` table are left out.
+    pub fn load(paths: &[PathBuf], schemas: &SchemaRegistry) -> Self {
+        let mut localization = Self::default();
+        for path in paths {
+            match schemas.load_document(path) {
+                Ok(document) if locales_of(&document).is_some() => {
+                    localization.documents.push(LocalizedDocument {
+                        path: path.clone(),
+                        saved: document.clone(),
+                        document,
+                        dirty: false,
+                    });
+                }
+                Ok(_) => {}
+                Err(err) => localization
+                    .errors
+                    .push(format!("{}: {}", path.display(), err)),
+            }
+        }
+        localization.rebuild();
+        localization
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    pub fn locales(&self) -> &[String] {
+        &self.locales
+    }
+
+    pub fn document_count(&self) -> usize {
+        self.documents.len()
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.documents.iter().any(|d| d.dirty)
+    }
+
+    /// Row of the translation key `key`, e.g. `jade_gold.name`.
+    pub fn entry_index(&self, key: &str) -> Option<usize> {
+        self.entries.iter().position(|entry| entry.key == key)
+    }
+
+    pub fn locale_index(&self, locale: &str) -> Option<usize> {
+        self.locales.iter().position(|l| l == locale)
+    }
+
+    /// Path of the document `entry` belongs to.
+    pub fn path_of(&self, entry: &Entry) -> Option<&PathBuf> {
+        self.documents.get(entry.document).map(|d| &d.path)
+    }
+
+    /// Recomputes rows, locale columns and duplicates from the documents.
+    fn rebuild(&mut self) {
+        self.entries.clear();
+        self.locales.clear();
+        for (index, localized) in self.documents.iter().enumerate() {
+            let Some(locales) = locales_of(&localized.document) else {
+                continue;
+            };
+            let stem = localized
+                .path
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let key = match localized.document.get(KEY_FIELD).map(|f| &f.value) {
+                Some(FieldValue::String { value, .. }) if !value.is_empty() => value.clone(),
+                _ => stem,
+            };
+
+            let first_entry = self.entries.len();
+            for (locale, field) in locales {
+                if !self.locales.contains(locale) {
+                    self.locales.push(locale.clone());
+                }
+                let FieldValue::Table { children } = &field.value else {
+                    continue;
+                };
+                for (name, child) in children {
+                    let is_new = !self
+                        .entries
+                        .iter()
+                        .skip(first_entry)
+                        .any(|entry| entry.name == *name);
+                    if is_new && matches!(child.value, FieldValue::String { .. }) {
+                        self.entries.push(Entry {
+                            document: index,
+                            name: name.clone(),
+                            key: format!("{key}.{name}"),
+                            label: child.label.clone(),
+                        });
+                    }
+                }
+            }
+        }
+        self.find_duplicates();
+    }
+
+    /// The text of `entry` in `locale`.
+    pub fn cell(&self, entry: usize, locale: usize) -> Cell<'_> {
+        let (Some(entry), Some(locale)) = (self.entries.get(entry), self.locales.get(locale))
+        else {
+            return Cell::Missing;
+        };
+        let Some(localized) = self.documents.get(entry.document) else {
+            return Cell::Missing;
+        };
+        let path = join_path(&join_path(LOCALES_FIELD, locale), &entry.name);
+        match localized.document.get(&path).map(|f| &f.value) {
+            Some(FieldValue::String { value, .. }) if value.trim().is_empty() => Cell::Empty,
+            Some(FieldValue::String { value, .. }) => Cell::Text(value),
+            _ => Cell::Missing,
+        }
+    }
+
+    /// The entry whose text in `locale` is nearly the same as that of `entry`, if any.
+    pub fn duplicate_of(&self, entry: usize, locale: usize) -> Option<&Entry> {
+        self.duplicates
+            .get(&(entry, locale))
+            .and_then(|other| self.entries.get(*other))
+    }
+
+    /// Number of entries with a non-blank text in `locale`, and the number of entries.
+    pub fn completion(&self, locale: usize) -> (usize, usize) {
+        let done = (0..self.entries.len())
+            .filter(|entry| matches!(self.cell(*entry, locale), Cell::Text(_)))
+            .count();
+        (done, self.entries.len())
+    }
+
+    /// Sets the text of `entry` in `locale`, creating the locale table and the entry when
+    /// the document lacks them.
+    pub fn set(&mut self, entry: usize, locale: usize, text: &str) {
+        let (Some(entry), Some(locale)) = (self.entries.get(entry), self.locales.get(locale))
+        else {
+            return;
+        };
+        let Some(localized) = self.documents.get_mut(entry.document) else {
+            return;
+        };
+        let Some(FieldValue::Table { children: locales }) = localized
+            .document
+            .get_mut(LOCALES_FIELD)
+            .map(|f| &mut f.value)
+        else {
+            return;
+        };
+
+        let table = locales.entry(locale.clone()).or_insert_with(|| {
+            let mut table = Field::default_table();
+            table.label = Some(locale.clone());
+            table
+        });
+        let FieldValue::Table { children } = &mut table.value else {
+            return;
+        };
+        let field = children.entry(entry.name.clone()).or_insert_with(|| {
+            let mut field = Field::default_string();
+            field.label.clone_from(&entry.label);
+            field
+        });
+        if let FieldValue::String { value, .. } = &mut field.value {
+            text.clone_into(value);
+            localized.dirty = true;
+        }
+        self.find_duplicates();
+    }
+
+    /// Adds `locale` to every document, copying the entries of the document's first
+    /// locale with blank texts. Returns the number of documents changed.
+    pub fn add_locale(&mut self, locale: &str) -> usize {
+        let mut added = 0;
+        for localized in &mut self.documents {
+            let Some(FieldValue::Table { children: locales }) = localized
+                .document
+                .get_mut(LOCALES_FIELD)
+                .map(|f| &mut f.value)
+            else {
+                continue;
+            };
+            if locales.contains_key(locale) {
+                continue;
+            }
+            let mut table = match locales.first() {
+                Some((_, template)) => template.clone(),
+                None => Field::default_table(),
+            };
+            table.label = Some(locale.to_owned());
+            clear_strings(&mut table);
+            locales.insert(locale.to_owned(), table);
+            localized.dirty = true;
+            added += 1;
+        }
+        self.rebuild();
+        added
+    }
+
+    /// Writes every edited document as one undoable change.
+    pub fn save(&mut self) -> Option<Change> {
+        let mut files = Vec::new();
+        self.errors.clear();
+        for localized in self.documents.iter_mut().filter(|d| d.dirty) {
+            match localized.document.save(&localized.path) {
+                Ok(()) => {
+                    files.push(FileChange {
+                        path: localized.path.clone(),
+                        before: std::mem::replace(&mut localized.saved, localized.document.clone()),
+                        after: localized.document.clone(),
+                    });
+                    localized.dirty = false;
+                }
+                Err(err) => self
+                    .errors
+                    .push(format!("{}: {}", localized.path.display(), err)),
+            }
+        }
+        (!files.is_empty()).then(|| Change {
+            label: format!("Edit translations in {} files", files.len()),
+            files,
+        })
+    }
+
+    /// Flags entries whose texts in the same locale only differ in case, punctuation,
+    /// spacing or a typo.
+    fn find_duplicates(&mut self) {
+        self.duplicates.clear();
+        for locale in 0..self.locales.len() {
+            let texts: Vec<(usize, String)> = (0..self.entries.len())
+                .filter_map(|entry| match self.cell(entry, locale) {
+                    Cell::Text(text) => Some((entry, normalize(text))),
+                    Cell::Missing | Cell::Empty => None,
+                })
+                .collect();
+            for (i, (entry, text)) in texts.iter().enumerate() {
+                let similar = texts
+                    .iter()
+                    .enumerate()
+                    .find(|(j, (_, other))| *j != i && is_similar(text, other));
+                if let Some((_, (other, _))) = similar {
+                    self.duplicates.insert((*entry, locale), *other);
+                }
+            }
+        }
+    }
+}
+
+fn locales_of(document: &Document) -> Option<&indexmap::IndexMap<String, Field>> {
+    match &document.get(LOCALES_FIELD)?.value {
+        FieldValue::Table { children } => Some(children),
+        _ => None,
+    }
+}
+
+fn clear_strings(field: &mut Field) {
+    match &mut field.value {
+        FieldValue::String { value, .. } => value.clear(),
+        FieldValue::Table { children } => {
+            for (_, child) in children.iter_mut() {
+                clear_strings(child);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Lowercase letters and digits of `text`, words separated by single spaces.
+fn normalize(text: &str) -> String {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Equal once normalized, or one edit apart for texts of five characters or more.
+fn is_similar(a: &str, b: &str) -> bool {
+    if a == b {
+        return true;
+    }
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    a.len().min(b.len()) >= 5 && edit_distance(&a, &b) <= 1
+}
+
+/// Levenshtein distance between two character sequences.
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous.get(j).copied().unwrap_or_default() + usize::from(ca != cb);
+            let deletion = previous.get(j + 1).copied().unwrap_or_default() + 1;
+            let insertion = current.get(j).copied().unwrap_or_default() + 1;
+            current.push(substitution.min(deletion).min(insertion));
+        }
+        previous = current;
+    }
+    previous.last().copied().unwrap_or_default()
+}
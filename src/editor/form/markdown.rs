@@ -0,0 +1,144 @@
+/// A block of a field's Markdown help.
+///
+/// Help supports the common subset of Markdown: headings, paragraphs, bullet lists, code
+/// blocks, and inline code, emphasis, strong text and links.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Block {
+    /// `#` to `###`, with the level.
+    Heading(usize, Vec<Inline>),
+    Paragraph(Vec<Inline>),
+    /// A `-` or `*` list item.
+    Bullet(Vec<Inline>),
+    /// Lines between two ```` ``` ```` fences.
+    Code(String),
+}
+
+/// A run of text inside a block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Inline {
+    Text(String),
+    /// `**text**`
+    Strong(String),
+    /// `*text*` or `_text_`
+    Emphasis(String),
+    /// `` `text` ``
+    Code(String),
+    /// `[text](url)`
+    Link {
+        text: String,
+        url: String,
+    },
+}
+
+/// Splits `text` into blocks. Consecutive lines form one paragraph; blank lines end it.
+pub fn parse(text: &str) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut code: Option<Vec<&str>> = None;
+
+    let flush = |paragraph: &mut Vec<&str>, blocks: &mut Vec<Block>| {
+        if !paragraph.is_empty() {
+            blocks.push(Block::Paragraph(parse_inline(&paragraph.join(" "))));
+            paragraph.clear();
+        }
+    };
+
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            if let Some(lines) = code.take() {
+                blocks.push(Block::Code(lines.join("\n")));
+            } else {
+                flush(&mut paragraph, &mut blocks);
+                code = Some(Vec::new());
+            }
+            continue;
+        }
+        if let Some(lines) = &mut code {
+            lines.push(line);
+            continue;
+        }
+
+        if trimmed.is_empty() {
+            flush(&mut paragraph, &mut blocks);
+        } else if let Some((level, heading)) = heading(trimmed) {
+            flush(&mut paragraph, &mut blocks);
+            blocks.push(Block::Heading(level, parse_inline(heading)));
+        } else if let Some(item) = trimmed
+            .strip_prefix("- ")
+            .or_else(|| trimmed.strip_prefix("* "))
+        {
+            flush(&mut paragraph, &mut blocks);
+            blocks.push(Block::Bullet(parse_inline(item)));
+        } else {
+            paragraph.push(trimmed);
+        }
+    }
+    flush(&mut paragraph, &mut blocks);
+    // An unclosed fence runs to the end of the text.
+    if let Some(lines) = code {
+        blocks.push(Block::Code(lines.join("\n")));
+    }
+    blocks
+}
+
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    let text = line.get(level..)?.strip_prefix(' ')?;
+    (1..=3).contains(&level).then_some((level, text))
+}
+
+/// Splits a line into plain and formatted runs. Unmatched markers are kept as text.
+pub fn parse_inline(text: &str) -> Vec<Inline> {
+    let mut inlines = Vec::new();
+    let mut plain = String::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if let Some((inline, after)) = formatted(rest) {
+            if !plain.is_empty() {
+                inlines.push(Inline::Text(std::mem::take(&mut plain)));
+            }
+            inlines.push(inline);
+            rest = after;
+        } else {
+            plain.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    if !plain.is_empty() {
+        inlines.push(Inline::Text(plain));
+    }
+    inlines
+}
+
+/// The formatted run `text` starts with, and the text after it.
+fn formatted(text: &str) -> Option<(Inline, &str)> {
+    let enclosed = |open: &str, close: &str| {
+        let inner = text.strip_prefix(open)?;
+        let end = inner.find(close).filter(|end| *end > 0)?;
+        Some((inner[..end].to_owned(), &inner[end + close.len()..]))
+    };
+
+    if let Some((code, after)) = enclosed("`", "`") {
+        return Some((Inline::Code(code), after));
+    }
+    if let Some((strong, after)) = enclosed("**", "**") {
+        return Some((Inline::Strong(strong), after));
+    }
+    if let Some((emphasis, after)) = enclosed("*", "*").or_else(|| enclosed("_", "_")) {
+        return Some((Inline::Emphasis(emphasis), after));
+    }
+    let (link_text, after) = enclosed("[", "]")?;
+    let (url, after) = after.strip_prefix('(').and_then(|inner| {
+        inner
+            .find(')')
+            .map(|end| (&inner[..end], &inner[end + 1..]))
+    })?;
+    Some((
+        Inline::Link {
+            text: link_text,
+            url: url.to_owned(),
+        },
+        after,
+    ))
+}
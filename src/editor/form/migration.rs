@@ -0,0 +1,596 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+use toml::{Table, Value};
+
+use crate::editor::form::{
+    convert::convert_field,
+    form::{DocumentError, FieldType},
+    preserve,
+    schema::{SchemaError, SchemaRegistry},
+};
+
+/// Top-level key of a document naming the schema it was created from.
+pub const SCHEMA_KEY: &str = "schema";
+/// Field holding the format version, in schemas and documents alike. Its `value` is
+/// the version.
+pub const VERSION_PATH: &str = "meta.format_version";
+/// Top-level array of tables listing the migrations of a schema.
+pub const MIGRATIONS_KEY: &str = "migrations";
+
+/// One change made to documents older than `version`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Migration {
+    pub version: u32,
+    #[serde(flatten)]
+    pub step: MigrationStep,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum MigrationStep {
+    /// Renames a field, keeping it in the same table.
+    Rename { path: String, to: String },
+    /// Moves a field to another absolute path.
+    Move { path: String, to: String },
+    /// Changes the type of a field, converting its value.
+    Convert { path: String, to: FieldType },
+    /// Gives a field a value if it has none, adding it from the schema when missing.
+    SetDefault { path: String, value: Value },
+}
+
+/// The migrations needed to bring a document from one schema version to another.
+pub struct MigrationPlan {
+    pub schema: String,
+    pub from: u32,
+    pub to: u32,
+    pub migrations: Vec<Migration>,
+    schema_table: Table,
+}
+
+impl MigrationPlan {
+    /// Builds the plan for the document at `path`, or `None` if it is up to date or its
+    /// schema cannot be told.
+    ///
+    /// The schema is the document's `schema` or `extends` key. Documents without one get
+    /// the schema inferred from their folder or the project default, see
+    /// [`SchemaRegistry::infer_schema`].
+    ///
+    /// # Errors
+    /// Fails if the document's schema cannot be loaded or its migrations are malformed.
+    pub fn for_document(
+        schemas: &SchemaRegistry,
+        document: &Table,
+        path: &Path,
+    ) -> Result<Option<Self>, SchemaError> {
+        let named = document
+            .get(SCHEMA_KEY)
+            .or_else(|| document.get(super::schema::EXTENDS_KEY))
+            .and_then(Value::as_str)
+            .map(str::to_owned);
+        let Some(schema) = named.or_else(|| schemas.infer_schema(path)) else {
+            return Ok(None);
+        };
+
+        let schema_table = schemas.resolve_schema(&schema)?;
+        let to = version_of(&schema_table);
+        let from = version_of(document);
+        if from >= to {
+            return Ok(None);
+        }
+
+        let mut migrations: Vec<Migration> = match schema_table.get(MIGRATIONS_KEY) {
+            Some(value) => value
+                .clone()
+                .try_into()
+                .map_err(|err| SchemaError::Invalid(format!("{schema} migrations: {err}")))?,
+            None => Vec::new(),
+        };
+        migrations.retain(|m| m.version > from && m.version <= to);
+        migrations.sort_by_key(|m| m.version);
+
+        Ok(Some(Self {
+            schema,
+            from,
+            to,
+            migrations,
+            schema_table,
+        }))
+    }
+
+    /// Describes what `apply` would change, without touching `document`.
+    pub fn preview(&self, document: &Table) -> Vec<String> {
+        self.apply(&mut document.clone())
+    }
+
+    /// Migrates `document` and returns a description of every change.
+    pub fn apply(&self, document: &mut Table) -> Vec<String> {
+        let mut changes: Vec<String> = self
+            .migrations
+            .iter()
+            .map(|m| format!("v{}: {}", m.version, self.apply_step(document, &m.step)))
+            .collect();
+        self.set_version(document);
+        changes.push(format!("{VERSION_PATH}: {} -> {}", self.from, self.to));
+        changes
+    }
+
+    /// Writes the target version into the document's version field, adding the field
+    /// from the schema if the document predates it.
+    fn set_version(&self, document: &mut Table) {
+        let version = Value::Integer(self.to.into());
+        if let Some(slot) = version_slot(document) {
+            *slot = version;
+            return;
+        }
+        let mut field = self.schema_field(VERSION_PATH).unwrap_or_else(|| {
+            let mut field = Table::new();
+            field.insert("type".to_owned(), Value::String("integer".to_owned()));
+            field
+        });
+        field.insert("value".to_owned(), version);
+        if let Some((parent, key)) = create_parent(document, VERSION_PATH) {
+            parent.insert(key.to_owned(), Value::Table(field));
+        }
+    }
+
+    /// The definition of the field at `path` in the schema.
+    fn schema_field(&self, path: &str) -> Option<Table> {
+        let mut schema_table = self.schema_table.clone();
+        field_table(&mut schema_table, path).cloned()
+    }
+
+    fn apply_step(&self, document: &mut Table, step: &MigrationStep) -> String {
+        match step {
+            MigrationStep::Rename { path, to } => {
+                let Some((parent, key)) = parent_table(document, path) else {
+                    return format!("rename {path}: not present, skipped");
+                };
+                if !parent.contains_key(key) {
+                    return format!("rename {path}: not present, skipped");
+                }
+                if parent.contains_key(to) {
+                    return format!("rename {path}: {to} already exists, skipped");
+                }
+                let fields = std::mem::take(parent);
+                *parent = fields
+                    .into_iter()
+                    .map(|(k, v)| if k == key { (to.clone(), v) } else { (k, v) })
+                    .collect();
+                format!("rename {path} -> {to}")
+            }
+            MigrationStep::Move { path, to } => {
+                let Some(field) = parent_table(document, path).and_then(|(p, key)| p.remove(key))
+                else {
+                    return format!("move {path}: not present, skipped");
+                };
+                let Some((parent, key)) = create_parent(document, to) else {
+                    return format!("move {path}: cannot create {to}, skipped");
+                };
+                parent.insert(key.to_owned(), field);
+                format!("move {path} -> {to}")
+            }
+            MigrationStep::Convert { path, to } => match field_table(document, path) {
+                Some(field) => match convert_field(field, *to) {
+                    Ok(description) => format!("convert {path}: {description}"),
+                    Err(err) => format!("convert {path}: {err}, skipped"),
+                },
+                None => format!("convert {path}: not present, skipped"),
+            },
+            MigrationStep::SetDefault { path, value } => {
+                if let Some(field) = field_table(document, path) {
+                    if field.contains_key("value") {
+                        return format!("set default {path}: already set, skipped");
+                    }
+                    field.insert("value".to_owned(), value.clone());
+                    return format!("set default {path} = {value}");
+                }
+
+                let Some(mut field) = self.schema_field(path) else {
+                    return format!("set default {path}: not in schema {}, skipped", self.schema);
+                };
+                field.insert("value".to_owned(), value.clone());
+                match create_parent(document, path) {
+                    Some((parent, key)) => {
+                        parent.insert(key.to_owned(), Value::Table(field));
+                        format!("add {path} = {value}")
+                    }
+                    None => format!("set default {path}: cannot create field, skipped"),
+                }
+            }
+        }
+    }
+}
+
+/// The `meta.format_version` of a schema or document table. Tables without one are
+/// version 1.
+pub fn version_of(table: &Table) -> u32 {
+    version_value(table)
+        .and_then(|value| match value {
+            // Older schemas wrote the version as a float, e.g. `2.0`.
+            Value::Float(version) => Some(*version as i64),
+            other => other.as_integer(),
+        })
+        .and_then(|v| u32::try_from(v).ok())
+        .unwrap_or(1)
+}
+
+/// Gives a resolved document whose own table has no version, `original`, version 1
+/// instead of the version of the schema it extends: it predates versioning.
+pub fn keep_own_version(original: &Table, resolved: &mut Table) {
+    if version_value(original).is_some() {
+        return;
+    }
+    if let Some(slot) = version_slot(resolved) {
+        *slot = Value::Integer(1);
+    }
+}
+
+/// The version of a table: the `value` of its version field, or the key itself when it
+/// is a plain value, as in `[meta]` / `format_version = 2`.
+fn version_value(table: &Table) -> Option<&Value> {
+    let (parent, key) = VERSION_PATH.rsplit_once('.')?;
+    let parent = parent
+        .split('.')
+        .try_fold(table, |table, key| table.get(key)?.as_table())?;
+    match parent.get(key)? {
+        Value::Table(field) => field.get("value"),
+        plain => Some(plain),
+    }
+}
+
+/// Like [`version_value`], for writing the version in place. `None` if there is none.
+fn version_slot(table: &mut Table) -> Option<&mut Value> {
+    let (parent, key) = parent_table(table, VERSION_PATH)?;
+    match parent.get_mut(key)? {
+        Value::Table(field) => field.get_mut("value"),
+        plain => Some(plain),
+    }
+}
+
+/// The table holding the last segment of `path`, and that segment.
+fn parent_table<'a, 'p>(table: &'a mut Table, path: &'p str) -> Option<(&'a mut Table, &'p str)> {
+    let (parent, key) = match path.rsplit_once('.') {
+        Some((parent, key)) => (Some(parent), key),
+        None => (None, path),
+    };
+    let mut current = table;
+    for part in parent.into_iter().flat_map(|p| p.split('.')) {
+        current = current.get_mut(part)?.as_table_mut()?;
+    }
+    Some((current, key))
+}
+
+/// Like [`parent_table`], creating missing tables along the way.
+fn create_parent<'a, 'p>(table: &'a mut Table, path: &'p str) -> Option<(&'a mut Table, &'p str)> {
+    let (parent, key) = match path.rsplit_once('.') {
+        Some((parent, key)) => (Some(parent), key),
+        None => (None, path),
+    };
+    let mut current = table;
+    for part in parent.into_iter().flat_map(|p| p.split('.')) {
+        current = current
+            .entry(part)
+            .or_insert_with(|| {
+                let mut field = Table::new();
+                field.insert("type".to_owned(), Value::String("table".to_owned()));
+                Value::Table(field)
+            })
+            .as_table_mut()?;
+    }
+    Some((current, key))
+}
+
+fn field_table<'a>(table: &'a mut Table, path: &str) -> Option<&'a mut Table> {
+    let (parent, key) = parent_table(table, path)?;
+    parent.get_mut(key)?.as_table_mut()
+}
+
+// ==============================================
+// FILES
+// ==============================================
+
+/// Result of migrating one document file.
+pub struct MigrationReport {
+    pub path: PathBuf,
+    pub from: u32,
+    pub to: u32,
+    pub changes: Vec<String>,
+}
+
+/// Migrates the document at `path` if it is older than its schema. With `dry_run` the
+/// file is left untouched and the report only describes the changes.
+///
+/// # Errors
+/// Fails if the file cannot be read or written, or its schema cannot be loaded.
+pub fn migrate_file(
+    schemas: &SchemaRegistry,
+    path: &Path,
+    dry_run: bool,
+) -> Result<Option<MigrationReport>, DocumentError> {
+    let original = fs::read_to_string(path)?;
+    let mut document: Table = toml::from_str(&original)?;
+    let Some(plan) = MigrationPlan::for_document(schemas, &document, path)? else {
+        return Ok(None);
+    };
+
+    let changes = plan.apply(&mut document);
+    if !dry_run {
+        // Only the migrated values change; comments and formatting are kept.
+        let text = match preserve::update(&original, &document) {
+            Ok(text) => text,
+            Err(_) => toml::to_string(&document)?,
+        };
+        fs::write(path, text)?;
+    }
+    Ok(Some(MigrationReport {
+        path: path.to_path_buf(),
+        from: plan.from,
+        to: plan.to,
+        changes,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use toml::{Table, Value};
+
+    use super::{
+        Migration, MigrationPlan, MigrationStep, keep_own_version, migrate_file, version_of,
+    };
+    use crate::editor::form::{form::FieldType, schema::SchemaRegistry};
+
+    fn table(text: &str) -> Table {
+        toml::from_str(text).expect("valid TOML")
+    }
+
+    /// The value at a dotted `path` of `table`.
+    fn value_at<'a>(table: &'a Table, path: &str) -> Option<&'a Value> {
+        let mut keys = path.split('.');
+        let first = table.get(keys.next()?)?;
+        keys.try_fold(first, |value, key| value.get(key))
+    }
+
+    fn plan_for(document: &Table, path: &str) -> Option<MigrationPlan> {
+        MigrationPlan::for_document(&SchemaRegistry::default(), document, Path::new(path))
+            .expect("the bundled schemas load")
+    }
+
+    /// A plan to version 2 running `steps`, against a schema with a `data.hardness` field.
+    fn plan_with(steps: Vec<MigrationStep>) -> MigrationPlan {
+        MigrationPlan {
+            schema: "test".to_owned(),
+            from: 1,
+            to: 2,
+            migrations: steps
+                .into_iter()
+                .map(|step| Migration { version: 2, step })
+                .collect(),
+            schema_table: table("[data.hardness]\ntype = \"float\"\nvalue = 1.0"),
+        }
+    }
+
+    #[test]
+    fn reads_field_and_plain_versions() {
+        assert_eq!(version_of(&table("")), 1, "unversioned");
+        assert_eq!(
+            version_of(&table(
+                "[meta.format_version]\ntype = \"integer\"\nvalue = 3"
+            )),
+            3,
+            "field form"
+        );
+        assert_eq!(
+            version_of(&table("[meta]\nformat_version = 2")),
+            2,
+            "plain form"
+        );
+        assert_eq!(
+            version_of(&table("[meta]\nformat_version = 2.0")),
+            2,
+            "float"
+        );
+    }
+
+    #[test]
+    fn keeps_missing_version_out_of_resolved_documents() {
+        let mut resolved = table("[meta.format_version]\ntype = \"integer\"\nvalue = 3");
+        keep_own_version(&table("extends = \"block\""), &mut resolved);
+        assert_eq!(version_of(&resolved), 1, "inherited version is dropped");
+
+        let mut resolved = table("[meta]\nformat_version = 3");
+        keep_own_version(&table("[meta]\nformat_version = 3"), &mut resolved);
+        assert_eq!(version_of(&resolved), 3, "own version is kept");
+    }
+
+    #[test]
+    fn plans_migrations_from_the_named_schema() {
+        let document = table(
+            "schema = \"block\"\n\
+             [render]\ntype = \"table\"\n\
+             [render.tint]\ntype = \"color\"\nvalue = 0x123456",
+        );
+        let mut migrated = document.clone();
+        let plan = plan_for(&document, "stone.toml").expect("an unversioned block is outdated");
+        assert_eq!((plan.from, plan.to), (1, 3), "versions");
+        assert_eq!(
+            plan.preview(&document),
+            plan.apply(&mut migrated),
+            "preview"
+        );
+
+        let render = migrated
+            .get("render")
+            .and_then(Value::as_table)
+            .expect("render table");
+        assert_eq!(
+            value_at(render, "tint.value").and_then(Value::as_integer),
+            Some(0x12_3456),
+            "values are only set when missing"
+        );
+        assert_eq!(
+            value_at(render, "particle_color.value").and_then(Value::as_str),
+            Some("#ffffffff"),
+            "missing fields are added from the schema"
+        );
+        assert!(render.contains_key("bounds"), "later versions also apply");
+        assert_eq!(
+            value_at(&migrated, "meta.format_version.label").and_then(Value::as_str),
+            Some("Format Version"),
+            "the version field is added from the schema"
+        );
+        assert_eq!(version_of(&migrated), 3, "version is bumped");
+        assert!(plan_for(&migrated, "stone.toml").is_none(), "up to date");
+    }
+
+    #[test]
+    fn bumps_plain_versions_in_place() {
+        let mut document = table("schema = \"block\"\n[meta]\nformat_version = 2");
+        let plan = plan_for(&document, "stone.toml").expect("version 2 is outdated");
+        assert!(
+            plan.migrations.iter().all(|m| m.version == 3),
+            "only newer migrations run"
+        );
+        plan.apply(&mut document);
+        assert_eq!(
+            value_at(&document, "meta.format_version").and_then(Value::as_integer),
+            Some(3),
+            "written as a plain value"
+        );
+    }
+
+    #[test]
+    fn infers_the_schema_from_the_folder() {
+        let schemas = SchemaRegistry::default();
+        let infer = |path: &str| schemas.infer_schema(Path::new(path));
+        assert_eq!(
+            infer("mod/blocks/stone.toml").as_deref(),
+            Some("block"),
+            "plural"
+        );
+        assert_eq!(
+            infer("item/ores/gem.toml").as_deref(),
+            Some("item"),
+            "outer folder"
+        );
+        assert_eq!(
+            infer("misc/stone.toml"),
+            None,
+            "no default without a project"
+        );
+
+        let plan = plan_for(&table(""), "blocks/stone.toml").expect("inferred schema");
+        assert_eq!(plan.schema, "block", "plan schema");
+    }
+
+    #[test]
+    fn renames_moves_and_converts_fields() {
+        let mut document = table(
+            "[data]\ntype = \"table\"\n\
+             [data.strength]\ntype = \"float\"\nvalue = 2.6\n\
+             [data.light]\ntype = \"integer\"\nvalue = 4",
+        );
+        let plan = plan_with(vec![
+            MigrationStep::Rename {
+                path: "data.strength".to_owned(),
+                to: "hardness".to_owned(),
+            },
+            MigrationStep::Convert {
+                path: "data.hardness".to_owned(),
+                to: FieldType::Integer,
+            },
+            MigrationStep::Move {
+                path: "data.light".to_owned(),
+                to: "render.light".to_owned(),
+            },
+            MigrationStep::Move {
+                path: "data.missing".to_owned(),
+                to: "render.missing".to_owned(),
+            },
+        ]);
+        let changes = plan.apply(&mut document);
+
+        let data = document
+            .get("data")
+            .and_then(Value::as_table)
+            .expect("data table");
+        assert_eq!(
+            data.keys().collect::<Vec<_>>(),
+            ["type", "hardness"],
+            "renamed in place"
+        );
+        assert_eq!(
+            value_at(data, "hardness.value").and_then(Value::as_integer),
+            Some(3),
+            "converted"
+        );
+        assert_eq!(
+            value_at(&document, "render.light.value").and_then(Value::as_integer),
+            Some(4),
+            "moved into a new table"
+        );
+        assert_eq!(
+            changes.get(3).map(String::as_str),
+            Some("v2: move data.missing: not present, skipped"),
+            "missing fields are skipped"
+        );
+    }
+
+    #[test]
+    fn sets_defaults_from_the_schema() {
+        let mut document = Table::new();
+        let plan = plan_with(vec![
+            MigrationStep::SetDefault {
+                path: "data.hardness".to_owned(),
+                value: Value::Float(2.0),
+            },
+            MigrationStep::SetDefault {
+                path: "data.unknown".to_owned(),
+                value: Value::Float(2.0),
+            },
+        ]);
+        plan.apply(&mut document);
+        assert_eq!(
+            value_at(&document, "data.hardness.value").and_then(Value::as_float),
+            Some(2.0),
+            "added with the migration's value"
+        );
+        assert!(
+            value_at(&document, "data.unknown").is_none(),
+            "fields missing from the schema are not made up"
+        );
+    }
+
+    #[test]
+    fn migrates_files_keeping_comments() {
+        let dir = std::env::temp_dir().join(format!("atom_editor_migrate_{}", std::process::id()));
+        let path = dir.join("blocks").join("stone.toml");
+        fs::create_dir_all(path.parent().expect("has a folder")).expect("temp folder");
+        let original = "# Hand-written notes\n[meta]\nformat_version = 2 # bumped by hand\n";
+        fs::write(&path, original).expect("document written");
+
+        let schemas = SchemaRegistry::new(Some(&dir));
+        let dry_run = migrate_file(&schemas, &path, true).expect("migrates");
+        let text = fs::read_to_string(&path).expect("document read");
+        assert!(
+            dry_run.is_some() && text == original,
+            "dry runs leave the file alone"
+        );
+
+        let report = migrate_file(&schemas, &path, false)
+            .expect("migrates")
+            .expect("outdated");
+        let text = fs::read_to_string(&path).expect("document read");
+        fs::remove_dir_all(&dir).ok();
+        assert_eq!((report.from, report.to), (2, 3), "versions");
+        assert!(
+            text.starts_with("# Hand-written notes\n[meta]\nformat_version = 3 # bumped by hand\n"),
+            "comments survive, got:\n{text}"
+        );
+    }
+}
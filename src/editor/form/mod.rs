@@ -1 +1,18 @@
+pub mod color;
+pub mod compute;
+pub mod conditions;
+pub mod convert;
+pub mod diff;
+pub mod export;
+pub mod expr;
 pub mod form;
+pub mod formatting;
+pub mod localization;
+pub mod markdown;
+pub mod migration;
+pub mod options;
+pub mod preserve;
+pub mod schema;
+pub mod search;
+pub mod translations;
+pub mod vector;
@@ -0,0 +1,261 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+use crate::editor::form::{
+    expr::Value,
+    form::{Document, DocumentError},
+    schema::SchemaRegistry,
+};
+
+/// File at the root of a project holding project-wide settings.
+pub const MANIFEST_FILE: &str = "project.toml";
+
+/// Manifest written into new projects.
+pub const DEFAULT_MANIFEST: &str = r#"# Schema of documents that neither name one nor sit in a folder named after one,
+# e.g. `blocks/`. Used to migrate them.
+# default_schema = "block"
+
+# Leave fields that hold their schema default out of saved documents.
+# omit_defaults_on_save = true
+
+# Option sets shared by every document of the project. Enum fields use them with
+# `options_from = "<name>"`.
+[option_sets]
+# materials = ["stone", "wood", "metal"]
+# items = { field = "lang.key", schema = "item" }
+"#;
+
+/// A named list of enum options, referenced by `options_from`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum OptionSet {
+    /// A fixed list, e.g. `tags = ["a", "b"]`.
+    List(Vec<String>),
+    /// The values of `field` in every project document, or only in those of `schema`.
+    Query {
+        field: String,
+        schema: Option<String>,
+    },
+}
+
+/// The project manifest, `project.toml`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Manifest {
+    /// See [`SchemaRegistry::infer_schema`].
+    #[serde(default)]
+    pub default_schema: Option<String>,
+    /// See [`Document::omit_defaults_on_save`].
+    #[serde(default)]
+    pub omit_defaults_on_save: bool,
+    #[serde(default)]
+    pub option_sets: IndexMap<String, OptionSet>,
+}
+
+impl Manifest {
+    /// Reads the manifest of the project at `root`. A missing file is an empty manifest.
+    ///
+    /// # Errors
+    /// Fails if the file exists but cannot be read or parsed.
+    pub fn load(root: &Path) -> Result<Self, DocumentError> {
+        let path = root.join(MANIFEST_FILE);
+        if !path.is_file() {
+            return Ok(Self::default());
+        }
+        Ok(toml::from_str(&fs::read_to_string(path)?)?)
+    }
+}
+
+/// Everything option sets are resolved against: the manifest and the project documents.
+#[derive(Default)]
+pub struct ProjectOptions {
+    manifest: Manifest,
+    documents: Vec<Document>,
+}
+
+impl ProjectOptions {
+    /// Loads the manifest and every document of the project at `root`. Documents that
+    /// fail to load are skipped.
+    pub fn load(root: &Path, paths: &[PathBuf], schemas: &SchemaRegistry) -> Self {
+        let manifest = Manifest::load(root).unwrap_or_else(|err| {
+            log::error!("Failed to read {MANIFEST_FILE}: {err}");
+            Manifest::default()
+        });
+        let documents = paths
+            .iter()
+            .filter_map(|path| schemas.load_document(path).ok())
+            .collect();
+        Self {
+            manifest,
+            documents,
+        }
+    }
+
+    /// Resolves the sets visible from `document`: its own (from its schema) first, then
+    /// the manifest's.
+    pub fn resolve(&self, document: &Document) -> OptionSets {
+        let mut sets = HashMap::new();
+        let defined = document
+            .option_sets
+            .iter()
+            .chain(self.manifest.option_sets.iter());
+        for (name, set) in defined {
+            if !sets.contains_key(name) {
+                sets.insert(name.clone(), self.values(set));
+            }
+        }
+        OptionSets { sets }
+    }
+
+    fn values(&self, set: &OptionSet) -> Vec<String> {
+        match set {
+            OptionSet::List(values) => values.clone(),
+            OptionSet::Query { field, schema } => {
+                let mut values: Vec<String> = Vec::new();
+                for document in &self.documents {
+                    if schema.is_some() && document.schema != *schema {
+                        continue;
+                    }
+                    let value = document
+                        .get(field)
+                        .and_then(|f| Value::from_field(&f.value))
+                        .map(|v| v.to_text())
+                        .filter(|text| !text.is_empty());
+                    if let Some(value) = value {
+                        if !values.contains(&value) {
+                            values.push(value);
+                        }
+                    }
+                }
+                values
+            }
+        }
+    }
+}
+
+/// Option sets resolved to their values, keyed by name.
+#[derive(Default)]
+pub struct OptionSets {
+    sets: HashMap<String, Vec<String>>,
+}
+
+impl OptionSets {
+    pub fn get(&self, name: &str) -> Option<&[String]> {
+        self.sets.get(name).map(Vec::as_slice)
+    }
+
+    /// The options of an enum: those of its `options_from` set followed by its own.
+    pub fn options_for(&self, options_from: Option<&str>, own: &[String]) -> Vec<String> {
+        let mut options: Vec<String> = options_from
+            .and_then(|name| self.get(name))
+            .unwrap_or_default()
+            .to_vec();
+        for option in own {
+            if !options.contains(option) {
+                options.push(option.clone());
+            }
+        }
+        options
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Manifest, OptionSet, ProjectOptions};
+    use crate::editor::form::form::Document;
+
+    fn item(key: &str) -> Document {
+        Document::from_toml(&format!(
+            "schema = \"item\"\n[lang]\ntype = \"table\"\n[lang.key]\ntype = \"string\"\nvalue = \"{key}\""
+        ))
+        .expect("valid document")
+    }
+
+    fn project() -> ProjectOptions {
+        let manifest: Manifest = toml::from_str(
+            r#"
+[option_sets]
+materials = ["stone", "wood"]
+tags = ["from_manifest"]
+items = { field = "lang.key", schema = "item" }
+"#,
+        )
+        .expect("valid manifest");
+        let mut block = item("jade");
+        block.schema = Some("block".to_owned());
+        ProjectOptions {
+            manifest,
+            documents: vec![item("apple"), item(""), block, item("stick"), item("apple")],
+        }
+    }
+
+    #[test]
+    fn resolves_document_sets_before_the_manifest() {
+        let mut document = Document::from_toml("").expect("empty document");
+        document.option_sets.insert(
+            "tags".to_owned(),
+            OptionSet::List(vec!["from_schema".to_owned()]),
+        );
+        let sets = project().resolve(&document);
+        assert_eq!(
+            sets.get("tags"),
+            Some(["from_schema".to_owned()].as_slice()),
+            "the document's set wins"
+        );
+        assert_eq!(
+            sets.get("materials"),
+            Some(["stone".to_owned(), "wood".to_owned()].as_slice()),
+            "manifest sets are visible"
+        );
+        assert!(sets.get("unknown").is_none(), "undefined set");
+    }
+
+    #[test]
+    fn queries_field_values_of_project_documents() {
+        let document = Document::from_toml("").expect("empty document");
+        let sets = project().resolve(&document);
+        assert_eq!(
+            sets.get("items"),
+            Some(["apple".to_owned(), "stick".to_owned()].as_slice()),
+            "values of the schema's documents, once each, without blanks"
+        );
+    }
+
+    #[test]
+    fn lists_set_options_before_the_enum_own() {
+        let document = Document::from_toml("").expect("empty document");
+        let sets = project().resolve(&document);
+        let own = ["glass".to_owned(), "wood".to_owned()];
+        assert_eq!(
+            sets.options_for(Some("materials"), &own),
+            ["stone", "wood", "glass"],
+            "set first, own options not repeated"
+        );
+        assert_eq!(
+            sets.options_for(Some("unknown"), &own),
+            own,
+            "an undefined set adds nothing"
+        );
+        assert_eq!(
+            sets.options_for(None, &[]),
+            Vec::<String>::new(),
+            "no options"
+        );
+    }
+
+    #[test]
+    fn saves_enum_items_without_empty_options() {
+        let text = "[data]\ntype = \"table\"\n[data.tags]\ntype = \"array\"\narray_type = \"enums\"\noptions_from = \"tags\"\nitems = [{ value = \"a\" }]\n";
+        let document = Document::from_toml(text).expect("valid document");
+        let saved = document.to_toml().expect("serializable");
+        assert!(
+            !saved.contains("options = []"),
+            "items using the set have no options of their own: {saved}"
+        );
+    }
+}
@@ -0,0 +1,362 @@
+use toml_edit::{DocumentMut, Item, TableLike};
+
+/// Rewrites the TOML text `original` so it holds `new`, touching only what changed.
+///
+/// Comments, blank lines, key order and the formatting of unchanged values survive;
+/// changed values keep the comments around them, removed keys go away with theirs, keys
+/// `new` lists in another order move with theirs and new keys are appended to their
+/// table.
+///
+/// # Errors
+/// Fails if `original` is not valid TOML.
+pub fn update(original: &str, new: &toml::Table) -> Result<String, toml_edit::TomlError> {
+    let mut edit: DocumentMut = original.parse()?;
+    let old: toml::Table = toml::from_str(original).unwrap_or_default();
+    merge(edit.as_table_mut(), &old, new);
+    if is_reordered(&old, new) {
+        order_table(edit.as_table_mut(), new);
+    }
+    Ok(edit.to_string())
+}
+
+fn merge(edit: &mut dyn TableLike, old: &toml::Table, new: &toml::Table) {
+    let removed: Vec<String> = old
+        .keys()
+        .filter(|key| !new.contains_key(*key))
+        .cloned()
+        .collect();
+    for key in removed {
+        edit.remove(&key);
+    }
+
+    for (key, value) in new {
+        let old_value = old.get(key);
+        if old_value.is_some_and(|old| old == value && same_order(old, value)) {
+            continue;
+        }
+        match (edit.get_mut(key), old_value, value) {
+            (Some(item), Some(toml::Value::Table(old)), toml::Value::Table(new))
+                if item.is_table_like() =>
+            {
+                merge_table(item, old, new);
+            }
+            (Some(Item::Value(current)), _, value) => {
+                if let Ok(replacement) = to_item(value).into_value() {
+                    replace_value(current, replacement);
+                }
+            }
+            _ => {
+                edit.insert(key, to_item(value));
+            }
+        }
+    }
+}
+
+/// Merges `new` into a table item, then moves its keys into the order of `new`.
+fn merge_table(item: &mut Item, old: &toml::Table, new: &toml::Table) {
+    let reordered = is_reordered(old, new);
+    if let Some(table) = item.as_table_mut() {
+        merge(table, old, new);
+        if reordered {
+            order_table(table, new);
+        }
+    } else if let Some(table) = item.as_inline_table_mut() {
+        merge(table, old, new);
+        if reordered {
+            table.sort_values_by(|a, _, b, _| rank(new, a.get()).cmp(&rank(new, b.get())));
+        }
+    } else if let Some(table) = item.as_table_like_mut() {
+        merge(table, old, new);
+    }
+}
+
+/// Whether the keys `old` and `new` share are in another order in `new`.
+fn is_reordered(old: &toml::Table, new: &toml::Table) -> bool {
+    !old.keys()
+        .filter(|key| new.contains_key(*key))
+        .eq(new.keys().filter(|key| old.contains_key(*key)))
+}
+
+/// Whether the tables in two equal values list their keys in the same order; table
+/// equality ignores it.
+fn same_order(a: &toml::Value, b: &toml::Value) -> bool {
+    match (a, b) {
+        (toml::Value::Table(a), toml::Value::Table(b)) => {
+            a.keys().eq(b.keys()) && a.values().zip(b.values()).all(|(a, b)| same_order(a, b))
+        }
+        (toml::Value::Array(a), toml::Value::Array(b)) => {
+            a.iter().zip(b).all(|(a, b)| same_order(a, b))
+        }
+        _ => true,
+    }
+}
+
+/// Where `key` is in `new`; keys it lacks go last.
+fn rank(new: &toml::Table, key: &str) -> usize {
+    new.keys().position(|k| k == key).unwrap_or(usize::MAX)
+}
+
+/// Moves the entries of `table` into the order of `new`.
+///
+/// Values are sorted in place. `[headers]` are written in the order of their position in
+/// the document, so the positions of the tables under each entry are handed out again,
+/// one entry after the other, keeping their order within the entry.
+fn order_table(table: &mut toml_edit::Table, new: &toml::Table) {
+    table.sort_values_by(|a, _, b, _| rank(new, a.get()).cmp(&rank(new, b.get())));
+
+    let mut entries: Vec<Vec<isize>> = Vec::new();
+    for (_, item) in table.iter_mut() {
+        let mut positions = Vec::new();
+        for_each_positioned(item, &mut |table| positions.extend(table.position()));
+        positions.sort_unstable();
+        entries.push(positions);
+    }
+    let mut free = entries.concat();
+    free.sort_unstable();
+    let mut free = free.into_iter();
+    for ((_, item), positions) in table.iter_mut().zip(&entries) {
+        let assigned: Vec<isize> = free.by_ref().take(positions.len()).collect();
+        for_each_positioned(item, &mut |table| {
+            let new_position = table
+                .position()
+                .and_then(|position| positions.binary_search(&position).ok())
+                .and_then(|index| assigned.get(index));
+            if let Some(&position) = new_position {
+                table.set_position(position);
+            }
+        });
+    }
+}
+
+/// Calls `f` on every table under `item` that has a position in the document.
+fn for_each_positioned(item: &mut Item, f: &mut impl FnMut(&mut toml_edit::Table)) {
+    let tables: Vec<&mut toml_edit::Table> = match item {
+        Item::Table(table) => vec![table],
+        Item::ArrayOfTables(array) => array.iter_mut().collect(),
+        _ => Vec::new(),
+    };
+    for table in tables {
+        if table.position().is_some() {
+            f(table);
+        }
+        for (_, child) in table.iter_mut() {
+            for_each_positioned(child, f);
+        }
+    }
+}
+
+/// Replaces `current`, keeping its surrounding comments and, for arrays of the same
+/// length, the formatting of the elements that did not change.
+fn replace_value(current: &mut toml_edit::Value, new: toml_edit::Value) {
+    if let (toml_edit::Value::Array(items), toml_edit::Value::Array(new_items)) =
+        (&mut *current, &new)
+    {
+        if items.len() == new_items.len() {
+            for (item, new_item) in items.iter_mut().zip(new_items.iter()) {
+                if item.to_string().trim() != new_item.to_string().trim() {
+                    replace_value(item, new_item.clone());
+                }
+            }
+            return;
+        }
+    }
+    let decor = current.decor().clone();
+    *current = new;
+    *current.decor_mut() = decor;
+}
+
+/// Converts a value to an item laid out the way `toml::to_string` would: tables with
+/// headers, arrays of tables as `[[...]]`, everything else inline.
+fn to_item(value: &toml::Value) -> Item {
+    match value {
+        toml::Value::Table(table) => Item::Table(to_table(table)),
+        toml::Value::Array(items)
+            if !items.is_empty() && items.iter().all(toml::Value::is_table) =>
+        {
+            let mut array = toml_edit::ArrayOfTables::new();
+            for item in items {
+                if let toml::Value::Table(table) = item {
+                    array.push(to_table(table));
+                }
+            }
+            Item::ArrayOfTables(array)
+        }
+        other => Item::Value(to_value(other)),
+    }
+}
+
+fn to_table(table: &toml::Table) -> toml_edit::Table {
+    let mut edit = toml_edit::Table::new();
+    for (key, value) in table {
+        edit.insert(key, to_item(value));
+    }
+    edit
+}
+
+fn to_value(value: &toml::Value) -> toml_edit::Value {
+    match value {
+        toml::Value::String(text) => text.as_str().into(),
+        toml::Value::Integer(number) => (*number).into(),
+        toml::Value::Float(number) => (*number).into(),
+        toml::Value::Boolean(flag) => (*flag).into(),
+        toml::Value::Datetime(datetime) => datetime
+            .to_string()
+            .parse()
+            .unwrap_or_else(|_| datetime.to_string().into()),
+        toml::Value::Array(items) => items.iter().map(to_value).collect(),
+        toml::Value::Table(table) => {
+            let mut inline = toml_edit::InlineTable::new();
+            for (key, value) in table {
+                inline.insert(key, to_value(value));
+            }
+            toml_edit::Value::InlineTable(inline)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::update;
+
+    const ORIGINAL: &str = r#"# Stone block
+name = "stone" # internal
+hardness = 1.5
+
+# Looks
+[render]
+tint = 0xFFFFFF
+bounds = [0.0, 0.0, 0.0, 1.0, 1.0, 1.0]
+"#;
+
+    fn updated(edit: impl FnOnce(&mut toml::Table)) -> String {
+        let mut table: toml::Table = toml::from_str(ORIGINAL).expect("valid TOML");
+        edit(&mut table);
+        update(ORIGINAL, &table).expect("valid original")
+    }
+
+    #[test]
+    fn keeps_unchanged_text() {
+        assert_eq!(updated(|_| {}), ORIGINAL, "nothing changed");
+    }
+
+    #[test]
+    fn replaces_values_keeping_comments() {
+        let text = updated(|table| {
+            table.insert("name".to_owned(), "granite".into());
+        });
+        assert_eq!(
+            text,
+            ORIGINAL.replace("\"stone\" # internal", "\"granite\" # internal"),
+            "only the value changes"
+        );
+    }
+
+    #[test]
+    fn keeps_formatting_of_unchanged_array_items() {
+        let text = updated(|table| {
+            let bound = table
+                .get_mut("render")
+                .and_then(|render| render.get_mut("bounds"))
+                .and_then(|bounds| bounds.get_mut(4))
+                .expect("bounds have 6 items");
+            *bound = 0.5.into();
+        });
+        assert!(
+            text.contains("bounds = [0.0, 0.0, 0.0, 1.0, 0.5, 1.0]"),
+            "one item replaced, got:\n{text}"
+        );
+        assert!(text.contains("tint = 0xFFFFFF"), "hex integers kept");
+    }
+
+    #[test]
+    fn removes_and_appends_keys() {
+        let text = updated(|table| {
+            table.remove("hardness");
+            if let Some(render) = table.get_mut("render").and_then(toml::Value::as_table_mut) {
+                render.insert("solid".to_owned(), true.into());
+            }
+            let mut sound = toml::Table::new();
+            sound.insert("step".to_owned(), "stone".into());
+            table.insert("sound".to_owned(), sound.into());
+        });
+        assert!(!text.contains("hardness"), "removed key gone, got:\n{text}");
+        assert!(
+            text.contains("bounds = [0.0, 0.0, 0.0, 1.0, 1.0, 1.0]\nsolid = true\n"),
+            "new key appended to its table, got:\n{text}"
+        );
+        assert!(
+            text.ends_with("\n[sound]\nstep = \"stone\"\n"),
+            "new table written with a header, got:\n{text}"
+        );
+        let reparsed: toml::Table = toml::from_str(&text).expect("valid output");
+        assert_eq!(
+            reparsed
+                .get("sound")
+                .and_then(|sound| sound.get("step"))
+                .and_then(toml::Value::as_str),
+            Some("stone"),
+            "reparsed"
+        );
+    }
+
+    /// Moves `key` of `table` to `index`.
+    fn move_key(table: &mut toml::Table, key: &str, index: usize) {
+        let Some(value) = table.remove(key) else {
+            return;
+        };
+        let mut entries: Vec<_> = std::mem::take(table).into_iter().collect();
+        entries.insert(index, (key.to_owned(), value));
+        table.extend(entries);
+    }
+
+    #[test]
+    fn moves_keys() {
+        let text = updated(|table| {
+            move_key(table, "hardness", 0);
+            if let Some(render) = table.get_mut("render").and_then(toml::Value::as_table_mut) {
+                move_key(render, "bounds", 0);
+            }
+        });
+        assert_eq!(
+            text,
+            "hardness = 1.5\n# Stone block\nname = \"stone\" # internal\n\n\
+             # Looks\n[render]\nbounds = [0.0, 0.0, 0.0, 1.0, 1.0, 1.0]\ntint = 0xFFFFFF\n",
+            "keys move with their comments"
+        );
+    }
+
+    #[test]
+    fn moves_tables_with_their_sub_tables() {
+        let original = "[data]\n\n# Display name\n[data.name]\nvalue = \"stone\"\n\n\
+                        [data.sound]\nvalue = \"STONE\"\n\n[data.sound.volume]\nvalue = 1.0\n\n\
+                        [render]\ntint = 1\n";
+        let mut table: toml::Table = toml::from_str(original).expect("valid TOML");
+        if let Some(data) = table.get_mut("data").and_then(toml::Value::as_table_mut) {
+            move_key(data, "sound", 0);
+        }
+        let text = update(original, &table).expect("valid original");
+        assert_eq!(
+            text,
+            "[data]\n\n[data.sound]\nvalue = \"STONE\"\n\n[data.sound.volume]\nvalue = 1.0\n\n\
+             # Display name\n[data.name]\nvalue = \"stone\"\n\n[render]\ntint = 1\n",
+            "the table moves with its sub-table and comment"
+        );
+        let reparsed: toml::Table = toml::from_str(&text).expect("valid output");
+        assert_eq!(
+            reparsed
+                .get("data")
+                .and_then(toml::Value::as_table)
+                .map(|data| data.keys().cloned().collect::<Vec<_>>()),
+            Some(vec!["sound".to_owned(), "name".to_owned()]),
+            "reparsed in the new order"
+        );
+    }
+
+    #[test]
+    fn fails_on_invalid_original() {
+        assert!(
+            update("name = ", &toml::Table::new()).is_err(),
+            "invalid TOML is reported"
+        );
+    }
+}
@@ -0,0 +1,511 @@
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use toml::{Table, Value};
+
+use crate::editor::{
+    Assets,
+    form::{
+        form::{Document, DocumentError, Inherited},
+        migration::{MIGRATIONS_KEY, keep_own_version},
+        options::Manifest,
+    },
+};
+
+/// Top-level key naming the schema a document or schema inherits from.
+pub const EXTENDS_KEY: &str = "extends";
+/// Top-level key listing fragment files merged into a document or schema.
+pub const INCLUDE_KEY: &str = "include";
+
+const SCHEMA_SUFFIX: &str = ".schema.toml";
+
+#[derive(Debug)]
+pub enum SchemaError {
+    NotFound(String),
+    Cycle(String),
+    Invalid(String),
+    IoError(std::io::Error),
+    ParseError(toml::de::Error),
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound(name) => write!(f, "schema or fragment `{name}` not found"),
+            Self::Cycle(name) => write!(f, "`{name}` includes or extends itself"),
+            Self::Invalid(msg) => write!(f, "{msg}"),
+            Self::IoError(err) => write!(f, "{err}"),
+            Self::ParseError(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl From<std::io::Error> for SchemaError {
+    fn from(err: std::io::Error) -> Self {
+        Self::IoError(err)
+    }
+}
+
+impl From<toml::de::Error> for SchemaError {
+    fn from(err: toml::de::Error) -> Self {
+        Self::ParseError(err)
+    }
+}
+
+/// A schema or fragment file: its text and the folder its own includes are relative to.
+struct Source {
+    text: String,
+    dir: Option<PathBuf>,
+}
+
+/// Finds schemas by name and resolves `extends` / `include`.
+///
+/// Schemas are looked up in the project's `schemas` folder first and in the bundled
+/// assets second, so a project can override or extend the built-in `block` schema.
+#[derive(Default)]
+pub struct SchemaRegistry {
+    project_dir: Option<PathBuf>,
+}
+
+impl SchemaRegistry {
+    pub fn new(project_dir: Option<&Path>) -> Self {
+        Self {
+            project_dir: project_dir.map(Path::to_path_buf),
+        }
+    }
+
+    pub fn schema_dir(&self) -> Option<PathBuf> {
+        self.project_dir.as_ref().map(|dir| dir.join("schemas"))
+    }
+
+    /// Names of every available schema, project schemas first.
+    pub fn names(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        if let Some(Ok(entries)) = self.schema_dir().map(fs::read_dir) {
+            for entry in entries.flatten() {
+                let file_name = entry.file_name().to_string_lossy().into_owned();
+                if let Some(name) = file_name.strip_suffix(SCHEMA_SUFFIX) {
+                    names.push(name.to_owned());
+                }
+            }
+        }
+        for file in Assets::iter() {
+            if let Some(name) = file.strip_suffix(SCHEMA_SUFFIX) {
+                if !names.iter().any(|n| n == name) {
+                    names.push(name.to_owned());
+                }
+            }
+        }
+        names
+    }
+
+    /// Loads the fully resolved schema `name`.
+    ///
+    /// # Errors
+    /// Fails if the schema, or anything it extends or includes, is missing or invalid.
+    pub fn load_schema(&self, name: &str) -> Result<Document, DocumentError> {
+        let source = self.schema_source(name)?;
+        self.parse_document(&source.text, source.dir.as_deref())
+    }
+
+    /// The text of the file schema `name` is defined in, comments included.
+    ///
+    /// # Errors
+    /// Fails if the schema is missing or cannot be read.
+    pub fn schema_text(&self, name: &str) -> Result<String, SchemaError> {
+        Ok(self.schema_source(name)?.text)
+    }
+
+    /// The resolved TOML of schema `name`, including its version and migrations.
+    ///
+    /// # Errors
+    /// Fails if the schema, or anything it extends or includes, is missing or invalid.
+    pub fn resolve_schema(&self, name: &str) -> Result<Table, SchemaError> {
+        let source = self.schema_source(name)?;
+        self.resolve_source(&format!("schema:{name}"), &source, &mut Vec::new())
+    }
+
+    /// The schema of a document file that does not name one.
+    ///
+    /// The folders between the project root and the file are tried nearest first: a
+    /// folder named after a schema, or its plural, holds documents of that schema, e.g.
+    /// `blocks/stone.toml` is a `block`. Failing that, the manifest's `default_schema`.
+    pub fn infer_schema(&self, path: &Path) -> Option<String> {
+        let names = self.names();
+        let root = self.project_dir.as_deref();
+        let folders = path
+            .ancestors()
+            .skip(1)
+            .take_while(|dir| Some(*dir) != root)
+            .filter_map(|dir| dir.file_name()?.to_str());
+        for folder in folders {
+            let name = names.iter().find(|name| {
+                folder == name.as_str() || folder.strip_suffix('s') == Some(name.as_str())
+            });
+            if let Some(name) = name {
+                return Some(name.clone());
+            }
+        }
+
+        self.manifest().default_schema
+    }
+
+    /// The project manifest. Without a project, or if it cannot be read, the defaults.
+    fn manifest(&self) -> Manifest {
+        let Some(root) = &self.project_dir else {
+            return Manifest::default();
+        };
+        Manifest::load(root).unwrap_or_else(|err| {
+            log::error!("Failed to read the project manifest: {err}");
+            Manifest::default()
+        })
+    }
+
+    /// Loads a document file, resolving its `extends` and `include` keys.
+    ///
+    /// # Errors
+    /// Fails if the file cannot be read or does not resolve to a valid document.
+    pub fn load_document(&self, path: &Path) -> Result<Document, DocumentError> {
+        let text = fs::read_to_string(path)?;
+        self.parse_document(&text, path.parent())
+    }
+
+    /// Parses document text, resolving includes relative to `dir`.
+    ///
+    /// # Errors
+    /// Fails if the text does not resolve to a valid document.
+    pub fn parse_document(
+        &self,
+        text: &str,
+        dir: Option<&Path>,
+    ) -> Result<Document, DocumentError> {
+        let original: Table = toml::from_str(text)?;
+        let extends = original
+            .get(EXTENDS_KEY)
+            .and_then(Value::as_str)
+            .map(str::to_owned);
+
+        let mut table = self.resolve(original.clone(), dir, &mut Vec::new())?;
+        table.remove(MIGRATIONS_KEY);
+        // The version is never inherited: a document without one predates versioning.
+        keep_own_version(&original, &mut table);
+
+        let mut document = Document::from_table(table)?;
+        document.omit_defaults_on_save = self.manifest().omit_defaults_on_save;
+        document.inherited = self.inherited(&original, dir)?;
+        match (&document.schema, extends) {
+            (None, extends) => {
+                if let (Some(inherited), Some(name)) = (&mut document.inherited, &extends) {
+                    // Taken from `extends`, so not written back.
+                    inherited
+                        .base
+                        .insert("schema".to_owned(), name.as_str().into());
+                }
+                document.schema = extends;
+            }
+            // A document copied from its schema gets back what saving left out.
+            (Some(name), None) => match self.load_schema(name) {
+                Ok(schema) => document.restore_omitted(&schema),
+                Err(err) => log::error!("Failed to load schema {name}: {err}"),
+            },
+            (Some(_), Some(_)) => {}
+        }
+        Ok(document)
+    }
+
+    /// What the document file `original` inherits, if it extends or includes anything.
+    fn inherited(
+        &self,
+        original: &Table,
+        dir: Option<&Path>,
+    ) -> Result<Option<Inherited>, SchemaError> {
+        let (keys, own): (Table, Table) = original
+            .clone()
+            .into_iter()
+            .partition(|(key, _)| key == EXTENDS_KEY || key == INCLUDE_KEY);
+        if keys.is_empty() {
+            return Ok(None);
+        }
+        let mut base = self.resolve(keys.clone(), dir, &mut Vec::new())?;
+        base.remove(MIGRATIONS_KEY);
+        keep_own_version(original, &mut base);
+        // Compared with the saved document, so laid out the same way.
+        if let Some(table) = Document::from_table(base.clone())
+            .ok()
+            .and_then(|mut document| {
+                document.spell_out_unset();
+                Table::try_from(document).ok()
+            })
+        {
+            base = table;
+        }
+        Ok(Some(Inherited { keys, base, own }))
+    }
+
+    /// Merges the schema a table extends, then its fragments, then the table itself.
+    fn resolve(
+        &self,
+        mut table: Table,
+        dir: Option<&Path>,
+        stack: &mut Vec<String>,
+    ) -> Result<Table, SchemaError> {
+        let extends = table.remove(EXTENDS_KEY);
+        let includes = table.remove(INCLUDE_KEY);
+
+        let mut resolved = match extends {
+            Some(Value::String(name)) => {
+                let source = self.schema_source(&name)?;
+                self.resolve_source(&format!("schema:{name}"), &source, stack)?
+            }
+            Some(_) => {
+                return Err(SchemaError::Invalid(
+                    "`extends` must be a string".to_owned(),
+                ));
+            }
+            None => Table::new(),
+        };
+
+        let includes = match includes {
+            Some(Value::String(path)) => vec![path],
+            Some(Value::Array(items)) => items
+                .into_iter()
+                .map(|item| match item {
+                    Value::String(path) => Ok(path),
+                    _ => Err(SchemaError::Invalid("`include` must list paths".to_owned())),
+                })
+                .collect::<Result<_, _>>()?,
+            Some(_) => return Err(SchemaError::Invalid("`include` must list paths".to_owned())),
+            None => Vec::new(),
+        };
+        for include in includes {
+            let source = self.include_source(&include, dir)?;
+            let fragment = self.resolve_source(&format!("include:{include}"), &source, stack)?;
+            deep_merge(&mut resolved, fragment);
+        }
+
+        deep_merge(&mut resolved, table);
+        Ok(resolved)
+    }
+
+    fn resolve_source(
+        &self,
+        id: &str,
+        source: &Source,
+        stack: &mut Vec<String>,
+    ) -> Result<Table, SchemaError> {
+        if stack.iter().any(|s| s == id) {
+            return Err(SchemaError::Cycle(id.to_owned()));
+        }
+        stack.push(id.to_owned());
+        let table: Table = toml::from_str(&source.text)?;
+        let resolved = self.resolve(table, source.dir.as_deref(), stack);
+        stack.pop();
+        resolved
+    }
+
+    fn schema_source(&self, name: &str) -> Result<Source, SchemaError> {
+        let file_name = format!("{name}{SCHEMA_SUFFIX}");
+        if let Some(dir) = self.schema_dir() {
+            let path = dir.join(&file_name);
+            if path.is_file() {
+                return Ok(Source {
+                    text: fs::read_to_string(&path)?,
+                    dir: Some(dir),
+                });
+            }
+        }
+        Self::embedded_source(&file_name).ok_or_else(|| SchemaError::NotFound(name.to_owned()))
+    }
+
+    /// Fragments are looked up next to the including file, then in the project's
+    /// `schemas` folder, then in the bundled assets.
+    fn include_source(&self, include: &str, dir: Option<&Path>) -> Result<Source, SchemaError> {
+        let candidates = [dir.map(Path::to_path_buf), self.schema_dir()];
+        for base in candidates.into_iter().flatten() {
+            let path = base.join(include);
+            if path.is_file() {
+                return Ok(Source {
+                    text: fs::read_to_string(&path)?,
+                    dir: path.parent().map(Path::to_path_buf),
+                });
+            }
+        }
+        Self::embedded_source(include).ok_or_else(|| SchemaError::NotFound(include.to_owned()))
+    }
+
+    fn embedded_source(path: &str) -> Option<Source> {
+        let file = Assets::get(path)?;
+        Some(Source {
+            text: String::from_utf8_lossy(&file.data).into_owned(),
+            dir: None,
+        })
+    }
+}
+
+/// Merges `overlay` into `base`. Tables present in both are merged key by key; any other
+/// value in `overlay` replaces the one in `base`. New keys keep the order of `overlay`.
+pub fn deep_merge(base: &mut Table, overlay: Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(Value::Table(base_table)), Value::Table(overlay_table)) => {
+                deep_merge(base_table, overlay_table);
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// The inverse of [`deep_merge`]: the part of `table` that, merged into `base`, gives
+/// `table` back. Values equal to the base are left out, unless `own` has them too.
+pub fn overrides(table: Table, base: &Table, own: &Table) -> Table {
+    let empty = Table::new();
+    table
+        .into_iter()
+        .filter_map(|(key, value)| {
+            let own_value = own.get(&key);
+            match (value, base.get(&key)) {
+                (Value::Table(table), Some(Value::Table(base_table))) => {
+                    let own_table = own_value.and_then(Value::as_table).unwrap_or(&empty);
+                    let table = overrides(table, base_table, own_table);
+                    (!table.is_empty() || own_value.is_some()).then(|| (key, Value::Table(table)))
+                }
+                (value, base_value) if own_value.is_none() && base_value == Some(&value) => None,
+                (value, _) => Some((key, value)),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use toml::{Table, Value};
+
+    use super::{SchemaRegistry, deep_merge, overrides};
+    use crate::editor::form::form::{ArrayValue, FieldValue};
+
+    const ORE: &str = r#"# Harder than stone, and it glows
+extends = "block"
+
+[data.hardness]
+value = 3.0 # two pickaxe hits
+"#;
+
+    #[test]
+    fn merges_tables_key_by_key() {
+        let mut base: Table = toml::from_str(
+            "name = \"base\"\nlist = [1, 2]\n[data]\nhardness = 1.0\nlight = 0\n[data.sound]\nstep = \"stone\"",
+        )
+        .expect("valid TOML");
+        let overlay: Table = toml::from_str(
+            "list = [3]\nextra = true\n[data]\nlight = 15\n[data.sound]\nbreak = \"glass\"",
+        )
+        .expect("valid TOML");
+        deep_merge(&mut base, overlay);
+
+        let expected: Table = toml::from_str(
+            "name = \"base\"\nlist = [3]\nextra = true\n\
+             [data]\nhardness = 1.0\nlight = 15\n[data.sound]\nstep = \"stone\"\nbreak = \"glass\"",
+        )
+        .expect("valid TOML");
+        assert_eq!(base, expected, "arrays replaced, tables merged");
+        assert_eq!(
+            base.keys().collect::<Vec<_>>(),
+            ["name", "list", "data", "extra"],
+            "new keys go last"
+        );
+    }
+
+    #[test]
+    fn replaces_values_of_another_kind() {
+        let mut base: Table = toml::from_str("[data]\nhardness = 1.0").expect("valid TOML");
+        deep_merge(&mut base, toml::from_str("data = 3").expect("valid TOML"));
+        assert_eq!(
+            base.get("data").and_then(Value::as_integer),
+            Some(3),
+            "table replaced by a value"
+        );
+    }
+
+    #[test]
+    fn keeps_only_values_that_differ_from_the_base() {
+        let base: Table =
+            toml::from_str("name = \"base\"\nlist = [1]\n[data]\nhardness = 1.0\nlight = 0")
+                .expect("valid TOML");
+        let own: Table = toml::from_str("name = \"base\"").expect("valid TOML");
+        let table: Table = toml::from_str(
+            "name = \"base\"\nlist = [1]\nextra = true\n[data]\nhardness = 1.0\nlight = 15",
+        )
+        .expect("valid TOML");
+
+        let expected: Table = toml::from_str("name = \"base\"\nextra = true\n[data]\nlight = 15")
+            .expect("valid TOML");
+        assert_eq!(
+            overrides(table, &base, &own),
+            expected,
+            "own keys kept, inherited values left out"
+        );
+    }
+
+    #[test]
+    fn saves_extending_documents_as_overrides() {
+        let schemas = SchemaRegistry::default();
+        let mut document = schemas.parse_document(ORE, None).expect("valid document");
+        assert_eq!(
+            document.to_toml_preserving(ORE).expect("serializable"),
+            ORE,
+            "an unchanged document keeps its text"
+        );
+
+        if let Some(field) = document.get_mut("data.hardness") {
+            field.value = FieldValue::Float {
+                value: 4.0,
+                default: None,
+            };
+        }
+        if let Some(field) = document.get_mut("data.luminance") {
+            field.set_unset(false);
+        }
+        let text = document.to_toml_preserving(ORE).expect("serializable");
+        assert_eq!(
+            text,
+            format!(
+                "{}\n[data.luminance]\nunset = false\n",
+                ORE.replace("3.0", "4.0")
+            ),
+            "only the overrides are written"
+        );
+
+        let reloaded = schemas.parse_document(&text, None).expect("valid document");
+        assert_eq!(
+            reloaded.schema.as_deref(),
+            Some("block"),
+            "schema from `extends`"
+        );
+        assert!(
+            reloaded
+                .get("data.luminance")
+                .is_some_and(|f| !f.is_unset()),
+            "a set field stays set over the base's `unset`"
+        );
+    }
+
+    #[test]
+    fn loads_every_bundled_schema() {
+        let schemas = SchemaRegistry::default();
+        for name in schemas.names() {
+            assert!(schemas.load_schema(&name).is_ok(), "schema {name} loads");
+        }
+        let item = schemas.load_schema("item").expect("item schema");
+        assert!(
+            matches!(
+                item.get("recipe.workbench.pattern").map(|f| &f.value),
+                Some(FieldValue::Array { value: ArrayValue::Mixed { items } }) if items.iter().all(Value::is_array)
+            ),
+            "the pattern is an array of arrays"
+        );
+    }
+}
@@ -0,0 +1,141 @@
+use indexmap::IndexMap;
+
+use crate::editor::form::{
+    expr,
+    form::{ArrayValue, Document, Field, FieldValue, join_path},
+};
+
+/// Paths of the fields whose key, label, description or value contains `query`,
+/// ignoring case, in document order.
+///
+/// Fields inside object array items are included with the item index, e.g.
+/// `drops[0].item`. An empty query matches nothing.
+pub fn find(document: &Document, query: &str) -> Vec<String> {
+    let query = query.trim().to_lowercase();
+    let mut matches = Vec::new();
+    if !query.is_empty() {
+        find_in(&document.fields, "", &query, &mut matches);
+    }
+    matches
+}
+
+fn find_in(fields: &IndexMap<String, Field>, prefix: &str, query: &str, matches: &mut Vec<String>) {
+    for (key, field) in fields {
+        let path = join_path(prefix, key);
+        let texts = [
+            Some(key.clone()),
+            field.label.clone(),
+            field.description.clone(),
+            value_text(&field.value),
+        ];
+        if texts
+            .iter()
+            .flatten()
+            .any(|text| text.to_lowercase().contains(query))
+        {
+            matches.push(path.clone());
+        }
+        match &field.value {
+            FieldValue::Table { children } => find_in(children, &path, query, matches),
+            FieldValue::Array {
+                value: ArrayValue::Objects { items, .. },
+            } => {
+                for (i, item) in items.iter().enumerate() {
+                    find_in(item, &format!("{path}[{i}]"), query, matches);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The value of a field as searchable text. Arrays of scalars give their items joined
+/// by spaces; tables and object arrays have no text of their own.
+fn value_text(value: &FieldValue) -> Option<String> {
+    let join = |items: Vec<String>| Some(items.join(" "));
+    match value {
+        FieldValue::Array { value } => match value {
+            ArrayValue::Strings { items } => join(items.clone()),
+            ArrayValue::Enums { items, .. } => {
+                join(items.iter().map(|item| item.value.clone()).collect())
+            }
+            ArrayValue::Integers { items } => join(items.iter().map(i64::to_string).collect()),
+            ArrayValue::Floats { items } => join(items.iter().map(f64::to_string).collect()),
+            ArrayValue::Mixed { items } => join(items.iter().map(toml::Value::to_string).collect()),
+            ArrayValue::Objects { .. } => None,
+        },
+        FieldValue::Vec2 { value, .. } => join(value.iter().map(f64::to_string).collect()),
+        FieldValue::Vec3 { value, .. } => join(value.iter().map(f64::to_string).collect()),
+        FieldValue::Aabb { value, .. } => join(value.iter().map(f64::to_string).collect()),
+        other => expr::Value::from_field(other).map(|value| value.to_text()),
+    }
+}
+
+/// Paths of the tables and object array items containing the field at `path`,
+/// outermost first, e.g. `events` and `events.drops[0]` for `events.drops[0].item`.
+pub fn ancestors(path: &str) -> Vec<&str> {
+    path.match_indices('.')
+        .filter_map(|(at, _)| path.get(..at))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ancestors, find};
+    use crate::editor::form::form::Document;
+
+    const DOCUMENT: &str = r#"
+[data]
+type = "table"
+label = "Block Data"
+name = { type = "string", value = "Jade Gold" }
+hardness = { type = "float", value = 2.5, description = "How long mining takes" }
+tags = { type = "array", array_type = "strings", items = ["gem", "ore"] }
+
+[events]
+type = "table"
+
+[events.drops]
+type = "array"
+array_type = "objects"
+
+[[events.drops.items]]
+item = { type = "string", value = "jade_gem" }
+"#;
+
+    fn search(query: &str) -> Vec<String> {
+        find(
+            &Document::from_toml(DOCUMENT).expect("valid document"),
+            query,
+        )
+    }
+
+    #[test]
+    fn matches_keys_labels_descriptions_and_values() {
+        assert_eq!(search("hardness"), ["data.hardness"], "key");
+        assert_eq!(search("block data"), ["data"], "label, ignoring case");
+        assert_eq!(search("MINING"), ["data.hardness"], "description");
+        assert_eq!(search("2.5"), ["data.hardness"], "number value");
+        assert_eq!(search(" ore "), ["data.tags"], "array items, trimmed query");
+    }
+
+    #[test]
+    fn finds_fields_in_object_items_in_order() {
+        assert_eq!(
+            search("jade"),
+            ["data.name", "events.drops[0].item"],
+            "document order"
+        );
+        assert!(search("  ").is_empty(), "empty query");
+    }
+
+    #[test]
+    fn lists_ancestors_outermost_first() {
+        assert_eq!(
+            ancestors("events.drops[0].item"),
+            ["events", "events.drops[0]"],
+            "ancestors"
+        );
+        assert!(ancestors("name").is_empty(), "top-level field");
+    }
+}
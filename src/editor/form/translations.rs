@@ -0,0 +1,649 @@
+use std::{fmt, path::Path};
+
+use crate::editor::form::localization::{Cell, Localization};
+
+/// File formats translations are exchanged in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TranslationFormat {
+    /// BTA language file, `key=text` per line. One file per locale.
+    Lang,
+    /// One row per key, one column per locale.
+    Csv,
+    /// gettext catalog, the source locale as `msgid`. One file per locale.
+    Po,
+}
+
+impl TranslationFormat {
+    pub const ALL: [Self; 3] = [Self::Lang, Self::Csv, Self::Po];
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Lang => "lang",
+            Self::Csv => "csv",
+            Self::Po => "po",
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?;
+        Self::ALL
+            .into_iter()
+            .find(|format| format.extension().eq_ignore_ascii_case(extension))
+    }
+
+    /// Whether a file holds a single locale rather than all of them.
+    pub fn is_per_locale(self) -> bool {
+        !matches!(self, Self::Csv)
+    }
+}
+
+#[derive(Debug)]
+pub enum TranslationError {
+    /// A line that could not be read, with its 1-based number.
+    Parse { line: usize, message: String },
+    /// A per-locale file whose locale could not be told.
+    NoLocale,
+}
+
+impl fmt::Display for TranslationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse { line, message } => write!(f, "line {line}: {message}"),
+            Self::NoLocale => write!(f, "the file does not say which locale it holds"),
+        }
+    }
+}
+
+/// One translated string read from a file.
+struct Imported {
+    key: String,
+    locale: String,
+    text: String,
+    /// Text of the source locale when the file was exported.
+    source: Option<String>,
+}
+
+/// Outcome of an import.
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    pub imported: usize,
+    /// Locales the import added to the project.
+    pub new_locales: Vec<String>,
+    /// Keys of the file no project document has anymore.
+    pub unknown_keys: Vec<String>,
+    /// Keys whose source text changed since the file was exported.
+    pub changed_sources: Vec<String>,
+}
+
+/// Writes the translations of `locale` (every locale for CSV), next to the text of the
+/// source locale, the first one of the project.
+pub fn export(localization: &Localization, format: TranslationFormat, locale: usize) -> String {
+    match format {
+        TranslationFormat::Lang => export_lang(localization, locale),
+        TranslationFormat::Csv => export_csv(localization),
+        TranslationFormat::Po => export_po(localization, locale),
+    }
+}
+
+/// Reads `text` and writes its translations into `localization`. `locale` names the locale
+/// of per-locale files that do not name it themselves, usually the file name.
+///
+/// # Errors
+/// Fails if the file is malformed; nothing is imported then.
+pub fn import(
+    localization: &mut Localization,
+    format: TranslationFormat,
+    text: &str,
+    locale: Option<&str>,
+) -> Result<ImportReport, TranslationError> {
+    let imported = match format {
+        TranslationFormat::Lang => parse_lang(text, locale.ok_or(TranslationError::NoLocale)?)?,
+        TranslationFormat::Csv => parse_csv(text)?,
+        TranslationFormat::Po => parse_po(text, locale)?,
+    };
+
+    let mut report = ImportReport::default();
+    for entry in imported {
+        let Some(row) = localization.entry_index(&entry.key) else {
+            if !report.unknown_keys.contains(&entry.key) {
+                report.unknown_keys.push(entry.key);
+            }
+            continue;
+        };
+        if let Some(source) = &entry.source {
+            if source_text(localization, row) != source.as_str()
+                && !report.changed_sources.contains(&entry.key)
+            {
+                report.changed_sources.push(entry.key.clone());
+            }
+        }
+
+        if localization.locale_index(&entry.locale).is_none() {
+            localization.add_locale(&entry.locale);
+            report.new_locales.push(entry.locale.clone());
+        }
+        let Some(column) = localization.locale_index(&entry.locale) else {
+            continue;
+        };
+        // A PO file holds the source text as `msgid`, never as a translation.
+        if column == 0 && format == TranslationFormat::Po {
+            continue;
+        }
+        if cell_text(localization, row, column) != entry.text {
+            localization.set(row, column, &entry.text);
+        }
+        report.imported += 1;
+    }
+    Ok(report)
+}
+
+fn cell_text(localization: &Localization, entry: usize, locale: usize) -> &str {
+    match localization.cell(entry, locale) {
+        Cell::Text(text) => text,
+        Cell::Missing | Cell::Empty => "",
+    }
+}
+
+fn source_text(localization: &Localization, entry: usize) -> &str {
+    cell_text(localization, entry, 0)
+}
+
+/// Prefix of the comment a `.lang` export puts before each key with its source text.
+const LANG_SOURCE: &str = "#source ";
+
+fn export_lang(localization: &Localization, locale: usize) -> String {
+    let mut out = String::new();
+    for (row, entry) in localization.entries().iter().enumerate() {
+        if locale != 0 {
+            out.push_str(LANG_SOURCE);
+            out.push_str(&escape_line(source_text(localization, row)));
+            out.push('\n');
+        }
+        out.push_str(&entry.key);
+        out.push('=');
+        out.push_str(&escape_line(cell_text(localization, row, locale)));
+        out.push('\n');
+    }
+    out
+}
+
+fn parse_lang(text: &str, locale: &str) -> Result<Vec<Imported>, TranslationError> {
+    let mut imported = Vec::new();
+    let mut source = None;
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        if let Some(text) = line.strip_prefix(LANG_SOURCE) {
+            source = Some(unescape_line(text));
+            continue;
+        }
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(TranslationError::Parse {
+                line: number + 1,
+                message: "expected `key=text`".to_owned(),
+            });
+        };
+        imported.push(Imported {
+            key: key.trim().to_owned(),
+            locale: locale.to_owned(),
+            text: unescape_line(value),
+            source: source.take(),
+        });
+    }
+    Ok(imported)
+}
+
+/// `.lang` values are single lines, so line breaks are written as `\n`.
+fn escape_line(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn unescape_line(text: &str) -> String {
+    let mut out = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+fn export_csv(localization: &Localization) -> String {
+    let mut out = String::from("key");
+    for locale in localization.locales() {
+        out.push(',');
+        out.push_str(&csv_field(locale));
+    }
+    out.push('\n');
+    for (row, entry) in localization.entries().iter().enumerate() {
+        out.push_str(&csv_field(&entry.key));
+        for locale in 0..localization.locales().len() {
+            out.push(',');
+            out.push_str(&csv_field(cell_text(localization, row, locale)));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_owned()
+    }
+}
+
+/// Reads a CSV export. The second column is the source locale, so its texts are also
+/// checked against the project.
+fn parse_csv(text: &str) -> Result<Vec<Imported>, TranslationError> {
+    let mut rows = csv_rows(text)?.into_iter();
+    let Some(header) = rows.next() else {
+        return Ok(Vec::new());
+    };
+    let locales = header.get(1..).unwrap_or_default();
+
+    let mut imported = Vec::new();
+    for row in rows {
+        let mut cells = row.into_iter();
+        let Some(key) = cells.next().filter(|key| !key.is_empty()) else {
+            continue;
+        };
+        let texts: Vec<String> = cells.collect();
+        let source = texts.first().cloned();
+        // The source column itself is only compared, never written.
+        for (locale, text) in locales.iter().zip(texts).skip(1) {
+            imported.push(Imported {
+                key: key.clone(),
+                locale: locale.clone(),
+                text,
+                source: source.clone(),
+            });
+        }
+    }
+    Ok(imported)
+}
+
+fn csv_rows(text: &str) -> Result<Vec<Vec<String>>, TranslationError> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut line = 1;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (quoted, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            (true, '"') => quoted = false,
+            (true, c) => {
+                if c == '\n' {
+                    line += 1;
+                }
+                field.push(c);
+            }
+            (false, '"') if field.is_empty() => quoted = true,
+            (false, ',') => row.push(std::mem::take(&mut field)),
+            (false, '\n') => {
+                line += 1;
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            (false, '\r') => {}
+            (false, c) => field.push(c),
+        }
+    }
+    if quoted {
+        return Err(TranslationError::Parse {
+            line,
+            message: "unterminated quoted field".to_owned(),
+        });
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+fn export_po(localization: &Localization, locale: usize) -> String {
+    let name = localization
+        .locales()
+        .get(locale)
+        .map(String::as_str)
+        .unwrap_or_default();
+    let mut out = format!(
+        "msgid \"\"\nmsgstr \"\"\n\"Language: {name}\\n\"\n\"Content-Type: text/plain; charset=UTF-8\\n\"\n"
+    );
+    for (row, entry) in localization.entries().iter().enumerate() {
+        let text = if locale == 0 {
+            ""
+        } else {
+            cell_text(localization, row, locale)
+        };
+        out.push_str(&format!(
+            "\nmsgctxt {}\nmsgid {}\nmsgstr {}\n",
+            po_string(&entry.key),
+            po_string(source_text(localization, row)),
+            po_string(text)
+        ));
+    }
+    out
+}
+
+fn po_string(text: &str) -> String {
+    let escaped = text
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\t', "\\t");
+    format!("\"{escaped}\"")
+}
+
+/// Reads a PO catalog: `msgctxt` is the key, `msgid` the source text. The locale comes
+/// from the `Language` header, else from `locale`.
+///
+/// As in gettext, an empty `msgstr` is untranslated and a `#, fuzzy` one is a guess to
+/// review; neither is imported.
+fn parse_po(text: &str, locale: Option<&str>) -> Result<Vec<Imported>, TranslationError> {
+    #[derive(Default)]
+    struct Message {
+        context: Option<String>,
+        id: String,
+        text: String,
+        /// Marked `#, fuzzy`.
+        fuzzy: bool,
+        /// Set once `msgstr` is read; the next keyword or comment starts a new message.
+        done: bool,
+    }
+
+    let mut messages = Vec::new();
+    let mut message = Message::default();
+    // Keyword the continuation lines of a multi-line string belong to.
+    let mut current = "";
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        let parse_error = |message: &str| TranslationError::Parse {
+            line: number + 1,
+            message: message.to_owned(),
+        };
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(comment) = line.strip_prefix('#') {
+            if message.done {
+                messages.push(std::mem::take(&mut message));
+            }
+            if let Some(flags) = comment.strip_prefix(',') {
+                message.fuzzy |= flags.split(',').any(|flag| flag.trim() == "fuzzy");
+            }
+            continue;
+        }
+        let continuation = line.starts_with('"');
+        let (keyword, rest) = if continuation {
+            (current, line)
+        } else {
+            line.split_once(' ')
+                .ok_or_else(|| parse_error("expected a keyword and a string"))?
+        };
+        let string = po_unquote(rest).ok_or_else(|| parse_error("expected a quoted string"))?;
+        if !continuation && keyword != "msgstr" && message.done {
+            messages.push(std::mem::take(&mut message));
+        }
+        match (keyword, continuation) {
+            ("msgctxt", false) => message.context = Some(string),
+            ("msgid", false) => message.id = string,
+            ("msgstr", false) => {
+                message.text = string;
+                message.done = true;
+            }
+            ("msgctxt", true) => message.context.get_or_insert_default().push_str(&string),
+            ("msgid", true) => message.id.push_str(&string),
+            ("msgstr", true) => message.text.push_str(&string),
+            _ => return Err(parse_error("unsupported keyword")),
+        }
+        current = keyword;
+    }
+    messages.push(message);
+
+    let header = messages
+        .iter()
+        .find(|m| m.context.is_none() && m.id.is_empty())
+        .map(|m| m.text.as_str())
+        .unwrap_or_default();
+    let language = header
+        .lines()
+        .find_map(|line| line.strip_prefix("Language:"))
+        .map(str::trim)
+        .filter(|language| !language.is_empty());
+    let locale = language
+        .or(locale)
+        .ok_or(TranslationError::NoLocale)?
+        .to_owned();
+
+    Ok(messages
+        .into_iter()
+        .filter(|m| !m.fuzzy && !m.text.is_empty())
+        .filter_map(|m| {
+            Some(Imported {
+                key: m.context?,
+                locale: locale.clone(),
+                text: m.text,
+                source: Some(m.id),
+            })
+        })
+        .collect())
+}
+
+fn po_unquote(text: &str) -> Option<String> {
+    let inner = text.strip_prefix('"')?.strip_suffix('"')?;
+    let mut out = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next()? {
+            'n' => out.push('\n'),
+            't' => out.push('\t'),
+            other => out.push(other),
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{TranslationError, TranslationFormat, cell_text, export, import};
+    use crate::editor::form::{localization::Localization, schema::SchemaRegistry};
+
+    const DOCUMENT: &str = r#"
+[lang]
+type = "table"
+
+[lang.key]
+type = "string"
+value = "jade"
+
+[lang.locales]
+type = "table"
+
+[lang.locales.en_us]
+type = "table"
+name = { type = "string", value = "Jade" }
+desc = { type = "string", value = "A green, \"shiny\" stone\nfound deep" }
+
+[lang.locales.es_es]
+type = "table"
+name = { type = "string", value = "Jade" }
+desc = { type = "string", value = "Una piedra verde\\brillante" }
+"#;
+
+    const NAME: usize = 0;
+    const DESC: usize = 1;
+    const EN: usize = 0;
+    const ES: usize = 1;
+
+    /// The translations of a project holding only [`DOCUMENT`].
+    fn localization(test: &str) -> Localization {
+        let dir = std::env::temp_dir().join(format!(
+            "atom_editor_translations_{test}_{}",
+            std::process::id()
+        ));
+        fs::create_dir_all(&dir).expect("temp folder");
+        let path = dir.join("jade.toml");
+        fs::write(&path, DOCUMENT).expect("document written");
+        let localization = Localization::load(&[path], &SchemaRegistry::default());
+        fs::remove_dir_all(&dir).ok();
+        assert!(localization.errors.is_empty(), "{:?}", localization.errors);
+        localization
+    }
+
+    fn text(localization: &Localization, entry: usize, locale: usize) -> String {
+        cell_text(localization, entry, locale).to_owned()
+    }
+
+    /// Exports `locale`, overwrites it, and imports the export back.
+    fn round_trip(format: TranslationFormat, test: &str) {
+        let mut localization = localization(test);
+        let exported = export(&localization, format, ES);
+        let original = text(&localization, DESC, ES);
+        localization.set(DESC, ES, "changed");
+
+        let report =
+            import(&mut localization, format, &exported, Some("es_es")).expect("valid export");
+        assert_eq!(
+            text(&localization, DESC, ES),
+            original,
+            "{format:?} restores the text"
+        );
+        assert_eq!(
+            text(&localization, DESC, EN),
+            "A green, \"shiny\" stone\nfound deep",
+            "{format:?} leaves the source alone"
+        );
+        assert!(
+            report.unknown_keys.is_empty() && report.changed_sources.is_empty(),
+            "{format:?} export matches the project: {report:?}"
+        );
+    }
+
+    #[test]
+    fn round_trips_lang() {
+        round_trip(TranslationFormat::Lang, "lang");
+    }
+
+    #[test]
+    fn round_trips_csv() {
+        round_trip(TranslationFormat::Csv, "csv");
+    }
+
+    #[test]
+    fn round_trips_po() {
+        round_trip(TranslationFormat::Po, "po");
+    }
+
+    #[test]
+    fn tells_formats_by_extension() {
+        let format = |path: &str| TranslationFormat::from_path(std::path::Path::new(path));
+        assert_eq!(format("es_es.LANG"), Some(TranslationFormat::Lang), "case");
+        assert_eq!(format("all.csv"), Some(TranslationFormat::Csv), "csv");
+        assert_eq!(format("notes.txt"), None, "unknown");
+    }
+
+    #[test]
+    fn adds_locales_and_reports_unknown_and_changed_keys() {
+        let mut localization = localization("report");
+        let lang = "#source Jade\njade.name=Jade (fr)\n\
+                    #source An old text\njade.desc=Pierre\ngone.name=Nope\n";
+        let report = import(
+            &mut localization,
+            TranslationFormat::Lang,
+            lang,
+            Some("fr_fr"),
+        )
+        .expect("valid file");
+        let fr = localization.locale_index("fr_fr").expect("locale added");
+        assert_eq!(report.new_locales, ["fr_fr"], "new locales");
+        assert_eq!(report.unknown_keys, ["gone.name"], "unknown keys");
+        assert_eq!(report.changed_sources, ["jade.desc"], "changed sources");
+        assert_eq!(text(&localization, NAME, fr), "Jade (fr)", "imported");
+    }
+
+    #[test]
+    fn needs_a_locale_for_lang_files() {
+        let mut localization = localization("no_locale");
+        assert!(
+            matches!(
+                import(&mut localization, TranslationFormat::Lang, "a=b", None),
+                Err(TranslationError::NoLocale)
+            ),
+            "locale required"
+        );
+        assert!(
+            matches!(
+                import(
+                    &mut localization,
+                    TranslationFormat::Lang,
+                    "a=b\nbroken",
+                    Some("es")
+                ),
+                Err(TranslationError::Parse { line: 2, .. })
+            ),
+            "malformed lines are reported with their number"
+        );
+    }
+
+    #[test]
+    fn skips_untranslated_and_fuzzy_po_messages() {
+        let mut localization = localization("po_skip");
+        let po = r#"msgid ""
+msgstr ""
+"Language: es_es\n"
+
+#, fuzzy
+msgctxt "jade.name"
+msgid "Jade"
+msgstr "Jade (a guess)"
+
+msgctxt "jade.desc"
+msgid "A green, \"shiny\" stone\n"
+"found deep"
+msgstr ""
+"#;
+        let report = import(&mut localization, TranslationFormat::Po, po, None).expect("valid");
+        assert_eq!(report.imported, 0, "nothing imported");
+        assert_eq!(text(&localization, NAME, ES), "Jade", "fuzzy skipped");
+        assert_eq!(
+            text(&localization, DESC, ES),
+            "Una piedra verde\\brillante",
+            "untranslated skipped"
+        );
+    }
+
+    #[test]
+    fn never_imports_the_source_locale_from_po() {
+        let mut localization = localization("po_source");
+        let po = "msgid \"\"\nmsgstr \"\"\n\"Language: en_us\\n\"\n\n\
+                  msgctxt \"jade.name\"\nmsgid \"Jade\"\nmsgstr \"Overwritten\"\n";
+        let report = import(&mut localization, TranslationFormat::Po, po, Some("es_es"))
+            .expect("valid file");
+        assert_eq!(report.imported, 0, "nothing imported");
+        assert_eq!(text(&localization, NAME, EN), "Jade", "source kept");
+        assert_eq!(text(&localization, NAME, ES), "Jade", "header locale wins");
+    }
+}
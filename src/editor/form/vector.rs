@@ -0,0 +1,37 @@
+use std::ops::RangeInclusive;
+
+/// Names of the components of `vec2` and `vec3` fields.
+pub const AXES: [&str; 3] = ["x", "y", "z"];
+
+/// Components of an `aabb` value, stored as `[minX, minY, minZ, maxX, maxY, maxZ]`.
+pub const AABB_COMPONENTS: [&str; 6] = ["minX", "minY", "minZ", "maxX", "maxY", "maxZ"];
+
+/// The range every component of a vector field must stay in, from its `min` / `max`.
+pub fn range(min: Option<f64>, max: Option<f64>) -> RangeInclusive<f64> {
+    min.unwrap_or(f64::NEG_INFINITY)..=max.unwrap_or(f64::INFINITY)
+}
+
+/// Why `value` is not a valid box, if it is not: a component outside `range`, or a
+/// minimum above its maximum.
+pub fn aabb_error(value: &[f64; 6], range: &RangeInclusive<f64>) -> Option<String> {
+    if let Some((name, component)) = AABB_COMPONENTS
+        .iter()
+        .zip(value)
+        .find(|(_, component)| !range.contains(component))
+    {
+        return Some(format!(
+            "{name} = {component} is outside {}..{}",
+            range.start(),
+            range.end()
+        ));
+    }
+    let [min_x, min_y, min_z, max_x, max_y, max_z] = *value;
+    [
+        ("X", min_x, max_x),
+        ("Y", min_y, max_y),
+        ("Z", min_z, max_z),
+    ]
+    .into_iter()
+    .find(|(_, min, max)| min > max)
+    .map(|(axis, min, max)| format!("min{axis} = {min} is above max{axis} = {max}"))
+}
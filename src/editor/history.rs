@@ -0,0 +1,97 @@
+use std::path::PathBuf;
+
+use crate::editor::form::form::{Document, DocumentError};
+
+/// One document rewritten on disk, with its contents before and after the edit.
+pub struct FileChange {
+    pub path: PathBuf,
+    pub before: Document,
+    pub after: Document,
+}
+
+/// A single undoable operation, possibly touching several files.
+pub struct Change {
+    pub label: String,
+    pub files: Vec<FileChange>,
+}
+
+impl Change {
+    fn write(&self, undo: bool) -> Vec<(PathBuf, DocumentError)> {
+        let mut errors = Vec::new();
+        for file in &self.files {
+            let document = if undo { &file.before } else { &file.after };
+            if let Err(err) = document.save(&file.path) {
+                errors.push((file.path.clone(), err));
+            }
+        }
+        errors
+    }
+}
+
+#[derive(Default)]
+pub struct History {
+    undo: Vec<Change>,
+    redo: Vec<Change>,
+    /// Files rewritten since the last [`History::take_written`].
+    written: Vec<PathBuf>,
+}
+
+impl History {
+    /// Records a change whose files were already written.
+    pub fn push(&mut self, change: Change) {
+        self.record_written(&change);
+        self.undo.push(change);
+        self.redo.clear();
+    }
+
+    /// The files pushed, undone or redone since the last call, so views showing them can
+    /// read them again.
+    pub fn take_written(&mut self) -> Vec<PathBuf> {
+        std::mem::take(&mut self.written)
+    }
+
+    fn record_written(&mut self, change: &Change) {
+        for file in &change.files {
+            if !self.written.contains(&file.path) {
+                self.written.push(file.path.clone());
+            }
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn undo_label(&self) -> Option<&str> {
+        self.undo.last().map(|c| c.label.as_str())
+    }
+
+    pub fn redo_label(&self) -> Option<&str> {
+        self.redo.last().map(|c| c.label.as_str())
+    }
+
+    /// Restores the files of the last change and returns the ones that could not be written.
+    pub fn undo(&mut self) -> Vec<(PathBuf, DocumentError)> {
+        let Some(change) = self.undo.pop() else {
+            return Vec::new();
+        };
+        let errors = change.write(true);
+        self.record_written(&change);
+        self.redo.push(change);
+        errors
+    }
+
+    pub fn redo(&mut self) -> Vec<(PathBuf, DocumentError)> {
+        let Some(change) = self.redo.pop() else {
+            return Vec::new();
+        };
+        let errors = change.write(false);
+        self.record_written(&change);
+        self.undo.push(change);
+        errors
+    }
+}
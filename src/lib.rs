@@ -1,6 +1,7 @@
 #![warn(clippy::all, rust_2018_idioms)]
 
 pub mod editor;
+#[cfg(feature = "gui")]
 pub mod selector;
 
 #[derive(Clone, serde::Serialize, serde::Deserialize)]